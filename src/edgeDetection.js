@@ -18,7 +18,9 @@ import init, {
   erode as wasmErode,
   morphological_close as wasmMorphologicalClose,
   unsharp_mask as wasmUnsharpMask,
-  unsharp_mask_and_downscale as wasmUnsharpMaskAndDownscale
+  unsharp_mask_and_downscale as wasmUnsharpMaskAndDownscale,
+  SampleMode as WasmSampleMode,
+  score_document_corners as wasmScoreDocumentCorners
 } from '../wasm_blur/pkg/wasm_blur.js';
 
 // Initialize the wasm module
@@ -650,6 +652,8 @@ export function getWasmPreprocessingModule() {
     dilate: wasmDilate,
     unsharp_mask: wasmUnsharpMask,
     unsharp_mask_and_downscale: wasmUnsharpMaskAndDownscale,
+    SampleMode: WasmSampleMode,
+    score_document_corners: wasmScoreDocumentCorners,
   };
 }
 
@@ -170,7 +170,8 @@ async function prepareScaleAndGrayscale(image, maxDimension = 800, preEnhance =
         try {
           if (wasmModule && wasmModule.unsharp_mask_and_downscale) {
             grayscaleData = new Uint8ClampedArray(wasmModule.unsharp_mask_and_downscale(
-              rawGrayscale, originalWidth, originalHeight, targetWidth, targetHeight, amount, radius
+              rawGrayscale, originalWidth, originalHeight, targetWidth, targetHeight, amount, radius,
+              wasmModule.SampleMode.Area
             ));
           } else { throw new Error('WASM unavailable'); }
         } catch (e) {
@@ -183,7 +184,8 @@ async function prepareScaleAndGrayscale(image, maxDimension = 800, preEnhance =
         try {
           if (wasmModule && wasmModule.clahe_and_downscale) {
             grayscaleData = new Uint8ClampedArray(wasmModule.clahe_and_downscale(
-              rawGrayscale, originalWidth, originalHeight, targetWidth, targetHeight, tileGridX, tileGridY, clipLimit
+              rawGrayscale, originalWidth, originalHeight, targetWidth, targetHeight, tileGridX, tileGridY, clipLimit,
+              wasmModule.SampleMode.Area
             ));
           } else { throw new Error('WASM unavailable'); }
         } catch (e) {
@@ -393,7 +395,7 @@ async function detectDocumentInternal(grayscaleData, width, height, scaleFactor,
       timings.push({ step: 'Contour Filter (Enhanced)', ms: (performance.now() - t0).toFixed(2) });
 
       if (filtered) {
-        allCandidates.push({ ...filtered, strategy: 'enhanced' });
+        allCandidates.push({ ...filtered, strategy: 'enhanced', edges: preprocessed });
       }
     }
   } catch (e) {
@@ -438,7 +440,7 @@ async function detectDocumentInternal(grayscaleData, width, height, scaleFactor,
       const filtered = findDocumentContour(cannyContours, width, height, filterOptions);
 
       if (filtered) {
-        allCandidates.push({ ...filtered, strategy: 'canny-fallback' });
+        allCandidates.push({ ...filtered, strategy: 'canny-fallback', edges });
       }
       // Keep largest raw contour as fallback
       if (!fallbackContour) {
@@ -473,7 +475,7 @@ async function detectDocumentInternal(grayscaleData, width, height, scaleFactor,
     if (defaultContours && defaultContours.length > 0) {
       const filtered = findDocumentContour(defaultContours, width, height, filterOptions);
       if (filtered) {
-        allCandidates.push({ ...filtered, strategy: 'canny-default' });
+        allCandidates.push({ ...filtered, strategy: 'canny-default', edges });
       }
       // Keep largest raw contour as fallback
       if (!fallbackContour) {
@@ -484,6 +486,7 @@ async function detectDocumentInternal(grayscaleData, width, height, scaleFactor,
 
   // ===== Pick the best candidate across all strategies =====
   let documentContour = null;
+  let documentEdges = null;
   let cornerPoints = null;
 
   if (allCandidates.length > 0) {
@@ -491,6 +494,7 @@ async function detectDocumentInternal(grayscaleData, width, height, scaleFactor,
     allCandidates.sort((a, b) => b.score - a.score);
     const best = allCandidates[0];
     documentContour = best.contour;
+    documentEdges = best.edges;
     timings.push({ step: `Best Strategy: ${best.strategy} (score=${best.score.toFixed(3)})`, ms: '0.00' });
   } else if (fallbackContour) {
     // No candidate passed the filters — use the largest raw contour as a last resort
@@ -525,6 +529,19 @@ async function detectDocumentInternal(grayscaleData, width, height, scaleFactor,
     };
   }
 
+  // Score the quad against the edge map it was found in (0 = untrustworthy)
+  let confidence = null;
+  if (documentEdges && wasmModule?.score_document_corners) {
+    const { topLeft, topRight, bottomRight, bottomLeft } = cornerPoints;
+    const quad = new Float32Array([
+      topLeft.x, topLeft.y, topRight.x, topRight.y,
+      bottomRight.x, bottomRight.y, bottomLeft.x, bottomLeft.y,
+    ]);
+    const scored = wasmModule.score_document_corners(quad, documentEdges, width, height);
+    confidence = scored.confidence;
+    scored.free();
+  }
+
   // Scale corner points back to original image size
   let finalCorners = cornerPoints;
   if (scaleFactor !== 1) {
@@ -541,6 +558,7 @@ async function detectDocumentInternal(grayscaleData, width, height, scaleFactor,
     success: true,
     contour: documentContour,
     corners: finalCorners,
+    confidence,
     debug: debugInfo._timingsOnly ? null : debugInfo,
     timings: timings
   };
@@ -863,7 +881,7 @@ export async function extractDocument(image, corners, options = {}) {
  *   - output: 'canvas' | 'imagedata' | 'dataurl' (default: 'canvas')
  *   - debug: boolean
  *   - ...other detection options
- * @returns {Promise<{output, corners, contour, debug, success, message, timings}>}
+ * @returns {Promise<{output, corners, contour, confidence, debug, success, message, timings}>}
  */
 export async function scanDocument(image, options = {}) {
   const timings = [];
@@ -948,6 +966,7 @@ export async function scanDocument(image, options = {}) {
     output,
     corners: detection.corners,
     contour: detection.contour,
+    confidence: detection.confidence,
     debug: detection.debug,
     success: true,
     message: 'Document detected',
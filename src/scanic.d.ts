@@ -34,6 +34,8 @@ export interface ScannerResult {
   output: HTMLCanvasElement | ImageData | string | null;
   corners: CornerPoints | null;
   contour: Point[] | null;
+  /** Detection confidence in [0, 1] from quad geometry and edge support; null when unavailable. */
+  confidence?: number | null;
   debug: any | null;
   timings: Timing[];
 }
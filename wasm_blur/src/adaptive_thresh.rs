@@ -1,4 +1,11 @@
 use wasm_bindgen::prelude::*;
+use crate::histogram::{histogram, otsu_from_histogram};
+use crate::integral_image::IntegralImage;
+use crate::tiling::TileGrid;
+use crate::memory::packed_rows;
+use crate::gaussian_blur::blur;
+use crate::mask_ops::remove_small_objects;
+use crate::morphology::flatten_background;
 
 /// Adaptive thresholding (Gaussian variant)
 /// Compares each pixel against a locally blurred version with an offset.
@@ -20,11 +27,387 @@ pub fn adaptive_threshold(
     let mut output = vec![0u8; pixel_count];
 
     for i in 0..pixel_count {
-        let threshold = blurred[i] as i32 - offset;
-        let above = (input[i] as i32) > threshold;
+        output[i] = if is_foreground(input[i], blurred[i], offset, invert) { 255 } else { 0 };
+    }
+
+    output
+}
+
+/// Whether a pixel is set (255) by `adaptive_threshold`: above its blurred
+/// value minus `offset`, or at or below it with `invert`.
+#[inline]
+fn is_foreground(value: u8, blurred: u8, offset: i32, invert: bool) -> bool {
+    let threshold = blurred as i32 - offset;
+    let above = (value as i32) > threshold;
+    above != invert
+}
+
+/// `adaptive_threshold` for an input whose rows start `stride` bytes apart.
+/// `blurred` and the result are tightly packed, as returned by `blur_strided`.
+#[wasm_bindgen]
+pub fn adaptive_threshold_strided(
+    input: &[u8],
+    blurred: &[u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+    offset: i32,
+    invert: bool,
+) -> Vec<u8> {
+    let packed = packed_rows(input, width, height, stride);
+    adaptive_threshold(&packed, blurred, width, height, offset, invert)
+}
+
+/// Binary mask together with its foreground pixel count.
+#[wasm_bindgen]
+pub struct ThresholdResult {
+    mask: Vec<u8>,
+    foreground_count: u32,
+}
+
+#[wasm_bindgen]
+impl ThresholdResult {
+    #[wasm_bindgen(getter)]
+    pub fn mask(&self) -> Vec<u8> {
+        self.mask.clone()
+    }
+
+    /// Number of pixels set to 255 in the mask.
+    #[wasm_bindgen(getter)]
+    pub fn foreground_count(&self) -> u32 {
+        self.foreground_count
+    }
+}
+
+/// Same as `adaptive_threshold`, but also reports how many pixels ended up
+/// foreground (255), counted during the same pass. Useful for auto-tuning the
+/// offset, e.g. towards ~15% foreground for text.
+#[wasm_bindgen]
+pub fn adaptive_threshold_stats(
+    input: &[u8],
+    blurred: &[u8],
+    width: usize,
+    height: usize,
+    offset: i32,
+    invert: bool,
+) -> ThresholdResult {
+    let pixel_count = width * height;
+    if input.len() != pixel_count || blurred.len() != pixel_count {
+        panic!("Input array sizes don't match width * height");
+    }
+
+    let mut mask = vec![0u8; pixel_count];
+    let mut foreground_count = 0u32;
+
+    for i in 0..pixel_count {
+        if is_foreground(input[i], blurred[i], offset, invert) {
+            mask[i] = 255;
+            foreground_count += 1;
+        }
+    }
+
+    ThresholdResult { mask, foreground_count }
+}
+
+/// Bradley-Roth adaptive thresholding.
+/// A pixel is dark (0) when it is more than `t_percent` percent below the mean
+/// of the `window` x `window` square around it, otherwise 255 (swapped when
+/// `invert` is set), matching the polarity of `adaptive_threshold`.
+/// Local means come from an integral image, so the cost is independent of
+/// the window size.
+///
+/// # Arguments
+/// * `window` - Side length of the averaging window; 0 selects `width / 8`
+/// * `t_percent` - Required darkness below the local mean in percent (15 works well for documents)
+#[wasm_bindgen]
+pub fn bradley_threshold(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    window: usize,
+    t_percent: f32,
+    invert: bool,
+) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let window = if window == 0 { (width / 8).max(1) } else { window };
+    let half = window / 2;
+    let integral = IntegralImage::new(input, width, height, false);
+    let factor = (100.0 - t_percent as f64) / 100.0;
 
-        output[i] = if above != invert { 255 } else { 0 };
+    let mut output = vec![0u8; pixel_count];
+    for y in 0..height {
+        for x in 0..width {
+            let (sum, count) = integral.window_sum(x, y, half);
+            // v * count <= sum * factor, kept in the multiplied form to avoid a division
+            let dark = (input[y * width + x] as f64) * (count as f64) <= sum as f64 * factor;
+            output[y * width + x] = if dark != invert { 0 } else { 255 };
+        }
     }
 
     output
-}
\ No newline at end of file
+}
+
+/// Wolf-Jolion thresholding for degraded, low-contrast documents.
+/// A Sauvola variant that normalizes by the global minimum `M` and the largest
+/// local standard deviation `R` in the image:
+///
+/// `T = (1 - k) * m + k * M + k * (s / R) * (m - M)`
+///
+/// where `m` and `s` are the mean and standard deviation of the `window` x
+/// `window` neighbourhood. Pixels at or below `T` are dark (0), others 255,
+/// swapped when `invert` is set.
+///
+/// # Arguments
+/// * `window` - Side length of the local window (e.g. 15-41 pixels)
+/// * `k` - Sensitivity, typically 0.5
+#[wasm_bindgen]
+pub fn wolf_threshold(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    window: usize,
+    k: f32,
+    invert: bool,
+) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+    if pixel_count == 0 {
+        return Vec::new();
+    }
+
+    let half = window / 2;
+    let integral = IntegralImage::new(input, width, height, true);
+    let global_min = *input.iter().min().unwrap() as f64;
+    let k = k as f64;
+
+    // First pass: local statistics and the maximum local deviation
+    let mut means = vec![0.0f32; pixel_count];
+    let mut deviations = vec![0.0f32; pixel_count];
+    let mut max_dev = 0.0f64;
+    for y in 0..height {
+        for x in 0..width {
+            let (mean, variance) = integral.window_mean_variance(x, y, half);
+            let dev = variance.sqrt();
+            means[y * width + x] = mean as f32;
+            deviations[y * width + x] = dev as f32;
+            max_dev = max_dev.max(dev);
+        }
+    }
+
+    // Second pass: threshold against the normalized local statistics
+    let mut output = vec![0u8; pixel_count];
+    for i in 0..pixel_count {
+        let m = means[i] as f64;
+        let s = deviations[i] as f64;
+        let contrast = if max_dev > 0.0 { s / max_dev } else { 0.0 };
+        let threshold = (1.0 - k) * m + k * global_min + k * contrast * (m - global_min);
+        let dark = input[i] as f64 <= threshold;
+        output[i] = if dark != invert { 0 } else { 255 };
+    }
+
+    output
+}
+
+/// Sauvola thresholding, the standard local method for document images:
+///
+/// `T = m * (1 + k * (s / 128 - 1))`
+///
+/// where `m` and `s` are the mean and standard deviation of the `window` x
+/// `window` neighbourhood. Flat regions (low `s`) get a threshold well below
+/// their mean, so paper texture stays white. Pixels at or below `T` are dark
+/// (0), others 255, swapped when `invert` is set.
+///
+/// # Arguments
+/// * `window` - Side length of the local window (e.g. 15-41 pixels)
+/// * `k` - Sensitivity, typically 0.2-0.5; larger values keep less ink
+#[wasm_bindgen]
+pub fn sauvola_threshold(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    window: usize,
+    k: f32,
+    invert: bool,
+) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let half = window / 2;
+    let integral = IntegralImage::new(input, width, height, true);
+    let k = k as f64;
+
+    let mut output = vec![0u8; pixel_count];
+    for y in 0..height {
+        for x in 0..width {
+            let (mean, variance) = integral.window_mean_variance(x, y, half);
+            let threshold = mean * (1.0 + k * (variance.sqrt() / 128.0 - 1.0));
+            let dark = input[y * width + x] as f64 <= threshold;
+            output[y * width + x] = if dark != invert { 0 } else { 255 };
+        }
+    }
+
+    output
+}
+
+/// One-call black and white scan of a page with dark text on a light
+/// background, built from the individual steps with defaults scaled to the
+/// image (`s` is the shorter side):
+///
+/// 1. `flatten_background` with a kernel of `s / 20` (at least 15) to remove
+///    shading and uneven lighting
+/// 2. a 3x3 Gaussian `blur` against sensor noise and JPEG artifacts
+/// 3. `sauvola_threshold` with a window of `s / 30` (at least 15), `k = 0.2`
+/// 4. `remove_small_objects` dropping ink specks under 8 pixels
+///
+/// # Returns
+/// Binary image, ink 0 and paper 255
+#[wasm_bindgen]
+pub fn binarize_document(input: &[u8], width: usize, height: usize) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+
+    let short_side = width.min(height);
+    let flattened = flatten_background(input, width, height, (short_side / 20).max(15) | 1);
+    let smoothed = blur(&flattened, width, height, 3, 0.0);
+    // Inverted, so the ink is the foreground the speck removal works on
+    let ink = sauvola_threshold(&smoothed, width, height, (short_side / 30).max(15) | 1, 0.2, true);
+    remove_small_objects(&ink, width, height, 8)
+        .into_iter()
+        .map(|v| 255 - v)
+        .collect()
+}
+
+/// Otsu thresholding per tile with bilinear interpolation between tiles.
+/// The image is split into a `tile_x` x `tile_y` grid like `clahe`; each tile
+/// gets its own Otsu threshold, and every pixel is compared against the
+/// thresholds of the four surrounding tile centers blended by distance, so
+/// there are no seams at tile borders. Handles documents with spatially
+/// varying contrast better than a single global Otsu threshold.
+///
+/// Nearly uniform tiles (standard deviation below 8) carry no usable
+/// threshold and fall back to the global Otsu level, so blank paper doesn't
+/// break up into noise. Pixels at or below the threshold are dark (0), others
+/// 255, swapped when `invert` is set.
+#[wasm_bindgen]
+pub fn adaptive_otsu(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    tile_x: usize,
+    tile_y: usize,
+    invert: bool,
+) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let grid = TileGrid::new(width, height, tile_x, tile_y);
+    let global = otsu_from_histogram(&histogram(input)) as f32;
+
+    let mut thresholds = vec![global; grid.num_tiles()];
+    for (tile_idx, (x_start, y_start, x_end, y_end)) in grid.tiles() {
+        let mut hist = [0u32; 256];
+        for y in y_start..y_end {
+            for &v in &input[y * width + x_start..y * width + x_end] {
+                hist[v as usize] += 1;
+            }
+        }
+
+        let n = ((y_end - y_start) * (x_end - x_start)) as f64;
+        let mean = hist.iter().enumerate().map(|(v, &c)| v as f64 * c as f64).sum::<f64>() / n;
+        let variance = hist
+            .iter()
+            .enumerate()
+            .map(|(v, &c)| (v as f64 - mean) * (v as f64 - mean) * c as f64)
+            .sum::<f64>()
+            / n;
+        if variance >= 64.0 {
+            thresholds[tile_idx] = otsu_from_histogram(&hist) as f32;
+        }
+    }
+
+    let mut output = vec![0u8; pixel_count];
+    for y in 0..height {
+        for x in 0..width {
+            let threshold = grid.interpolate_tile_values(x, y, &thresholds);
+            let dark = input[y * width + x] as f32 <= threshold;
+            output[y * width + x] = if dark != invert { 0 } else { 255 };
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::histogram::otsu_threshold;
+
+    const PAGE_SIZE: usize = 128;
+
+    /// Page lit from the right: paper from 60 to 240 across the width, with
+    /// 2 px vertical strokes every 8 px at half the paper brightness. Returns
+    /// the image and the stroke mask.
+    fn gradient_page() -> (Vec<u8>, Vec<bool>) {
+        let mut image = vec![0u8; PAGE_SIZE * PAGE_SIZE];
+        let mut ink = vec![false; PAGE_SIZE * PAGE_SIZE];
+        for y in 0..PAGE_SIZE {
+            for x in 0..PAGE_SIZE {
+                let paper = 60.0 + 180.0 * x as f32 / (PAGE_SIZE - 1) as f32;
+                let stroke = x % 8 >= 6;
+                image[y * PAGE_SIZE + x] = if stroke { paper * 0.5 } else { paper } as u8;
+                ink[y * PAGE_SIZE + x] = stroke;
+            }
+        }
+        (image, ink)
+    }
+
+    /// Fraction of pixels whose 0 / 255 class differs from the stroke mask.
+    fn error_rate(mask: &[u8], ink: &[bool]) -> f32 {
+        mask.iter().zip(ink).filter(|&(&v, &stroke)| (v == 0) != stroke).count() as f32 / mask.len() as f32
+    }
+
+    /// Global Otsu on `gradient_page` turns the dim paper dark.
+    fn global_otsu_error(image: &[u8], ink: &[bool]) -> f32 {
+        let level = otsu_threshold(image, PAGE_SIZE, PAGE_SIZE);
+        let global: Vec<u8> = image.iter().map(|&v| if v <= level { 0 } else { 255 }).collect();
+        error_rate(&global, ink)
+    }
+
+    #[test]
+    fn test_bradley_threshold_lighting_gradient() {
+        let (image, ink) = gradient_page();
+        let mask = bradley_threshold(&image, PAGE_SIZE, PAGE_SIZE, 16, 15.0, false);
+        assert!(global_otsu_error(&image, &ink) > 0.2);
+        assert!(error_rate(&mask, &ink) < 0.01);
+    }
+
+    #[test]
+    fn test_wolf_threshold_lighting_gradient() {
+        let (image, ink) = gradient_page();
+        let mask = wolf_threshold(&image, PAGE_SIZE, PAGE_SIZE, 15, 0.5, false);
+        assert!(global_otsu_error(&image, &ink) > 0.2);
+        assert!(error_rate(&mask, &ink) < 0.01, "{}", error_rate(&mask, &ink));
+    }
+
+    #[test]
+    fn test_adaptive_otsu_lighting_gradient() {
+        let (image, ink) = gradient_page();
+        let mask = adaptive_otsu(&image, PAGE_SIZE, PAGE_SIZE, 8, 8, false);
+        assert!(global_otsu_error(&image, &ink) > 0.2);
+        assert!(error_rate(&mask, &ink) < 0.01, "{}", error_rate(&mask, &ink));
+    }
+}
@@ -0,0 +1,104 @@
+use std::sync::OnceLock;
+use wasm_bindgen::prelude::*;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use crate::clahe::{clahe_inplace_with, column_weights};
+use crate::gaussian_blur::blur;
+use crate::tiling::TileGrid;
+use crate::unsharp_mask::unsharp_mask;
+
+/// Runs `process` on every `width` x `height` image of the contiguous stack
+/// `images`, each writing its result into the matching slot of the output
+/// stack. With the `rayon` feature the images are spread over the thread
+/// pool, one image per task; otherwise they run one after another.
+fn process_batch(
+    images: &[u8],
+    count: usize,
+    width: usize,
+    height: usize,
+    process: impl Fn(&[u8], &mut [u8]) + Sync,
+) -> Vec<u8> {
+    let frame = width * height;
+    if images.len() != count * frame {
+        panic!("Input array size doesn't match count * width * height");
+    }
+    let mut output = vec![0u8; images.len()];
+    if frame == 0 {
+        return output;
+    }
+
+    #[cfg(feature = "rayon")]
+    output
+        .par_chunks_mut(frame)
+        .zip(images.par_chunks(frame))
+        .for_each(|(out, image)| process(image, out));
+    #[cfg(not(feature = "rayon"))]
+    for (out, image) in output.chunks_mut(frame).zip(images.chunks(frame)) {
+        process(image, out);
+    }
+
+    output
+}
+
+/// `clahe` over a stack of `count` same-sized images stored back to back
+/// (`count * width * height` bytes), e.g. a folder of scanned pages, in a
+/// single call. The tile grid and interpolation weights are computed once
+/// for the whole batch and every page is equalized directly in its output
+/// slot. With the `rayon` feature pages are processed in parallel.
+#[wasm_bindgen]
+pub fn clahe_batch(
+    images: &[u8],
+    count: usize,
+    width: usize,
+    height: usize,
+    tile_grid_x: usize,
+    tile_grid_y: usize,
+    clip_limit: f32,
+) -> Vec<u8> {
+    // Built with the first image, after `process_batch` has checked the
+    // sizes; an empty batch never needs a tile grid
+    let tables = OnceLock::new();
+    process_batch(images, count, width, height, |image, out| {
+        let (grid, columns) = tables.get_or_init(|| {
+            let grid = TileGrid::new(width, height, tile_grid_x, tile_grid_y);
+            let columns = column_weights(&grid, 0..width);
+            (grid, columns)
+        });
+        out.copy_from_slice(image);
+        clahe_inplace_with(out, grid, columns, clip_limit);
+    })
+}
+
+/// `blur` over a stack of `count` same-sized images, like `clahe_batch`.
+#[wasm_bindgen]
+pub fn blur_batch(
+    images: &[u8],
+    count: usize,
+    width: usize,
+    height: usize,
+    kernel_size: usize,
+    sigma: f32,
+) -> Vec<u8> {
+    if kernel_size == 0 || kernel_size.is_multiple_of(2) {
+        panic!("Kernel size must be odd and greater than 0");
+    }
+    process_batch(images, count, width, height, |image, out| {
+        out.copy_from_slice(&blur(image, width, height, kernel_size, sigma));
+    })
+}
+
+/// `unsharp_mask` over a stack of `count` same-sized images, like
+/// `clahe_batch`.
+#[wasm_bindgen]
+pub fn unsharp_mask_batch(
+    images: &[u8],
+    count: usize,
+    width: usize,
+    height: usize,
+    amount: f32,
+    radius: usize,
+) -> Vec<u8> {
+    process_batch(images, count, width, height, |image, out| {
+        out.copy_from_slice(&unsharp_mask(image, width, height, amount, radius));
+    })
+}
@@ -0,0 +1,83 @@
+use wasm_bindgen::prelude::*;
+
+/// How filters sample pixels that fall outside the image.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BorderMode {
+    /// Replicate the nearest edge pixel (`aaa|abcd|ddd`)
+    Clamp,
+    /// Mirror without repeating the edge pixel (`cb|abcd|cb`), OpenCV's BORDER_REFLECT_101
+    Reflect,
+    /// Treat everything outside the image as 0
+    Zero,
+    /// Continue from the opposite edge (`cd|abcd|ab`), for tileable textures
+    Wrap,
+}
+
+/// Maps a possibly out-of-range coordinate onto `0..len` according to `mode`.
+/// Returns `None` when the sample should be treated as zero.
+#[inline]
+pub(crate) fn resolve(pos: isize, len: usize, mode: BorderMode) -> Option<usize> {
+    let n = len as isize;
+    if pos >= 0 && pos < n {
+        return Some(pos as usize);
+    }
+    match mode {
+        BorderMode::Clamp => Some(pos.clamp(0, n - 1) as usize),
+        BorderMode::Reflect => {
+            if n == 1 {
+                return Some(0);
+            }
+            // Reflection is periodic with period 2 * (n - 1)
+            let period = 2 * (n - 1);
+            let mut p = pos.rem_euclid(period);
+            if p >= n {
+                p = period - p;
+            }
+            Some(p as usize)
+        }
+        BorderMode::Zero => None,
+        BorderMode::Wrap => Some(pos.rem_euclid(n) as usize),
+    }
+}
+
+/// Copy of the image with `pad` extra pixels on every side, filled according
+/// to `mode`. Running a clamping filter whose reach is at most `pad` on the
+/// padded image and cropping the center gives the filter with `mode` borders.
+pub(crate) fn pad_image(input: &[u8], width: usize, height: usize, pad: usize, mode: BorderMode) -> Vec<u8> {
+    let padded_width = width + 2 * pad;
+    let mut output = Vec::with_capacity(padded_width * (height + 2 * pad));
+    for py in 0..height + 2 * pad {
+        let y = resolve(py as isize - pad as isize, height, mode);
+        for px in 0..padded_width {
+            let x = resolve(px as isize - pad as isize, width, mode);
+            output.push(match (x, y) {
+                (Some(x), Some(y)) => input[y * width + x],
+                _ => 0,
+            });
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_modes() {
+        // abcd with two samples on each side
+        let clamp: Vec<_> = (-2..6).map(|p| resolve(p, 4, BorderMode::Clamp)).collect();
+        assert_eq!(clamp, vec![Some(0), Some(0), Some(0), Some(1), Some(2), Some(3), Some(3), Some(3)]);
+
+        let reflect: Vec<_> = (-2..6).map(|p| resolve(p, 4, BorderMode::Reflect)).collect();
+        assert_eq!(reflect, vec![Some(2), Some(1), Some(0), Some(1), Some(2), Some(3), Some(2), Some(1)]);
+
+        assert_eq!(resolve(-1, 4, BorderMode::Zero), None);
+        assert_eq!(resolve(4, 4, BorderMode::Zero), None);
+        assert_eq!(resolve(-3, 1, BorderMode::Reflect), Some(0));
+
+        let wrap: Vec<_> = (-2..6).map(|p| resolve(p, 4, BorderMode::Wrap)).collect();
+        assert_eq!(wrap, vec![Some(2), Some(3), Some(0), Some(1), Some(2), Some(3), Some(0), Some(1)]);
+    }
+}
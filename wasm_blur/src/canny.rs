@@ -1,4 +1,5 @@
 use wasm_bindgen::prelude::*;
+use crate::color::{rgba_to_gray, Luma};
 
 // Hysteresis thresholding implementation, a key part of the Canny algorithm.
 fn hysteresis_thresholding(
@@ -110,3 +111,23 @@ pub fn canny_edge_detector_full(
 
     canny_edges
 }
+
+/// `canny_edge_detector_full` on an RGBA image, converted to gray with the
+/// `luma` weights first (see `rgba_to_gray`); no dilation is applied.
+// Mirrors the `canny_edge_detector_full` parameters, plus the luma weights
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn canny_rgba(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    low_threshold: f32,
+    high_threshold: f32,
+    kernel_size: usize,
+    sigma: f32,
+    l2_gradient: bool,
+    luma: Luma,
+) -> Vec<u8> {
+    let gray = rgba_to_gray(input, width, height, luma);
+    canny_edge_detector_full(&gray, width, height, low_threshold, high_threshold, kernel_size, sigma, l2_gradient, false, 0)
+}
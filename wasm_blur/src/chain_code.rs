@@ -0,0 +1,76 @@
+use wasm_bindgen::prelude::*;
+
+/// Freeman directions in image coordinates (y down): 0 = east, then
+/// counter-clockwise as seen on screen, so 2 = north and 6 = south.
+const DIRECTIONS: [(i64, i64); 8] = [(1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1), (0, 1), (1, 1)];
+
+/// Freeman chain code of a closed contour given as `[x0, y0, x1, y1, ...]`
+/// (the boundary order produced by contour tracing). Each code is one
+/// 8-connected step, see `DIRECTIONS`; the final steps lead from the last
+/// point back to the first. Consecutive points may be further apart as long
+/// as they lie on a horizontal, vertical or diagonal line, so simplified
+/// contours encode to the same chain as the full boundary.
+#[wasm_bindgen]
+pub fn chain_code(contour: &[u32]) -> Vec<u8> {
+    if !contour.len().is_multiple_of(2) {
+        panic!("Contour array must hold (x, y) pairs");
+    }
+
+    let points: Vec<(i64, i64)> = contour.chunks_exact(2).map(|p| (p[0] as i64, p[1] as i64)).collect();
+    let mut codes = Vec::new();
+    for (i, &(x0, y0)) in points.iter().enumerate() {
+        let (x1, y1) = points[(i + 1) % points.len()];
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        if dx != 0 && dy != 0 && dx.abs() != dy.abs() {
+            panic!("Consecutive contour points must lie on an 8-connected line");
+        }
+        let step = (dx.signum(), dy.signum());
+        if let Some(code) = DIRECTIONS.iter().position(|&d| d == step) {
+            codes.extend(std::iter::repeat_n(code as u8, dx.abs().max(dy.abs()) as usize));
+        }
+    }
+
+    codes
+}
+
+/// Inverse of `chain_code`: walks `codes` from (`start_x`, `start_y`) and
+/// returns the visited points as `[x0, y0, x1, y1, ...]`, one per step. A
+/// closed chain returns to the start, so the final point is dropped when it
+/// equals the first.
+#[wasm_bindgen]
+pub fn chain_decode(start_x: u32, start_y: u32, codes: &[u8]) -> Vec<u32> {
+    let (mut x, mut y) = (start_x as i64, start_y as i64);
+    let mut points = vec![start_x, start_y];
+    for &code in codes {
+        let (dx, dy) = match DIRECTIONS.get(code as usize) {
+            Some(&d) => d,
+            None => panic!("Chain codes must be in 0..8"),
+        };
+        x += dx;
+        y += dy;
+        if x < 0 || y < 0 || x > u32::MAX as i64 || y > u32::MAX as i64 {
+            panic!("Chain code leaves the coordinate range");
+        }
+        points.extend([x as u32, y as u32]);
+    }
+    if points.len() > 2 && points[points.len() - 2..] == points[..2] {
+        points.truncate(points.len() - 2);
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_code_round_trip() {
+        // 2x2 square corners, simplified: expands to one step per pixel
+        let contour = [1, 1, 3, 1, 3, 3, 1, 3];
+        let codes = chain_code(&contour);
+        assert_eq!(codes, vec![0, 0, 6, 6, 4, 4, 2, 2]);
+        let decoded = chain_decode(1, 1, &codes);
+        assert_eq!(decoded, vec![1, 1, 2, 1, 3, 1, 3, 2, 3, 3, 2, 3, 1, 3, 1, 2]);
+    }
+}
@@ -0,0 +1,337 @@
+use wasm_bindgen::prelude::*;
+use crate::adaptive_thresh::bradley_threshold;
+use crate::histogram::{histogram, otsu_from_histogram};
+use crate::morphology::erode;
+use crate::warp::{find_homography, perspective_transform_points};
+
+type Point = (f64, f64);
+
+/// Erosion sizes tried in turn to pull apart dark squares that touch at
+/// their corners; blurrier captures need the larger ones.
+const SEPARATIONS: [usize; 3] = [3, 5, 7];
+
+/// Smallest dark component (in pixels, after erosion) taken as a square.
+const MIN_SQUARE_AREA: usize = 16;
+
+/// Largest distance (in grid cells) a point may land from its grid position
+/// once the outer corners are mapped onto the ideal grid.
+const MAX_GRID_RESIDUAL: f64 = 0.3;
+
+/// A dark square of the board: its four corners in cyclic order and mean
+/// side length.
+struct Square {
+    corners: [Point; 4],
+    side: f64,
+}
+
+/// The four corners of a roughly quadrilateral point set, in cyclic order:
+/// the point farthest from the centroid, the point farthest from that one,
+/// and the points farthest from the diagonal between them on either side.
+/// Works at any rotation. `None` when the set is (nearly) a line.
+fn quad_corners(points: &[Point]) -> Option<[Point; 4]> {
+    let n = points.len() as f64;
+    let centroid = points.iter().fold((0.0, 0.0), |acc, p| (acc.0 + p.0 / n, acc.1 + p.1 / n));
+    let dist2 = |a: Point, b: Point| (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2);
+    let farthest_from = |q: Point| *points.iter().max_by(|a, b| dist2(**a, q).total_cmp(&dist2(**b, q))).unwrap();
+
+    let p0 = farthest_from(centroid);
+    let p2 = farthest_from(p0);
+    let side = |p: &Point| (p2.0 - p0.0) * (p.1 - p0.1) - (p2.1 - p0.1) * (p.0 - p0.0);
+    let p1 = *points.iter().max_by(|a, b| side(a).total_cmp(&side(b)))?;
+    let p3 = *points.iter().min_by(|a, b| side(a).total_cmp(&side(b)))?;
+
+    // Each off-diagonal corner must stand clear of the diagonal
+    let diagonal = dist2(p0, p2).sqrt();
+    if diagonal < 2.0 || side(&p1) / diagonal < 1.0 || -side(&p3) / diagonal < 1.0 {
+        return None;
+    }
+    Some([p0, p1, p2, p3])
+}
+
+/// Dark squares of a binary mask (dark = 255), as 8-connected components
+/// that look like quadrilaterals: their pixel count is close to the area of
+/// the quad spanned by their corners.
+fn find_squares(mask: &[u8], width: usize, height: usize) -> Vec<Square> {
+    let mut visited = vec![false; width * height];
+    let mut stack = Vec::new();
+    let mut squares = Vec::new();
+    for start in 0..width * height {
+        if visited[start] || mask[start] == 0 {
+            continue;
+        }
+        visited[start] = true;
+        stack.push(start);
+        let mut pixels = Vec::new();
+        while let Some(idx) = stack.pop() {
+            let (x, y) = (idx % width, idx / width);
+            pixels.push((x as f64, y as f64));
+            for ny in y.saturating_sub(1)..(y + 2).min(height) {
+                for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                    let nidx = ny * width + nx;
+                    if !visited[nidx] && mask[nidx] != 0 {
+                        visited[nidx] = true;
+                        stack.push(nidx);
+                    }
+                }
+            }
+        }
+        if pixels.len() < MIN_SQUARE_AREA {
+            continue;
+        }
+        let Some(corners) = quad_corners(&pixels) else {
+            continue;
+        };
+
+        // Shoelace area through the corner pixel centers, which runs half a
+        // pixel inside the component's outline
+        let area = (0..4)
+            .map(|i| {
+                let (a, b) = (corners[i], corners[(i + 1) % 4]);
+                a.0 * b.1 - b.0 * a.1
+            })
+            .sum::<f64>()
+            .abs()
+            / 2.0;
+        let perimeter: f64 = (0..4)
+            .map(|i| {
+                let (a, b) = (corners[i], corners[(i + 1) % 4]);
+                ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+            })
+            .sum();
+        let fill = pixels.len() as f64 / (area + perimeter / 2.0 + 1.0);
+        if (0.85..=1.15).contains(&fill) {
+            squares.push(Square { corners, side: perimeter / 4.0 });
+        }
+    }
+    squares
+}
+
+/// Inner chessboard corners: the midpoints of corner pairs from two
+/// different squares that are each other's nearest neighbour and close
+/// enough to have met before `separation` pulled them apart.
+fn pair_corners(squares: &[Square], separation: usize) -> Vec<Point> {
+    let corners: Vec<(usize, Point)> =
+        squares.iter().enumerate().flat_map(|(i, s)| s.corners.iter().map(move |&c| (i, c))).collect();
+    let nearest = |i: usize| {
+        let (square, p) = corners[i];
+        corners
+            .iter()
+            .enumerate()
+            .filter(|(_, (other, _))| *other != square)
+            .map(|(j, (_, q))| (j, ((p.0 - q.0).powi(2) + (p.1 - q.1).powi(2)).sqrt()))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    };
+
+    let mut points = Vec::new();
+    for i in 0..corners.len() {
+        let Some((j, distance)) = nearest(i) else {
+            continue;
+        };
+        let max_distance =
+            0.5 * squares[corners[i].0].side.min(squares[corners[j].0].side) + 1.5 * separation as f64;
+        if j > i && distance < max_distance && nearest(j).map(|(k, _)| k) == Some(i) {
+            let (p, q) = (corners[i].1, corners[j].1);
+            points.push(((p.0 + q.0) / 2.0, (p.1 + q.1) / 2.0));
+        }
+    }
+    points
+}
+
+/// Arranges exactly `cols * rows` points into a grid, row-major. The outer
+/// corners of the point set are mapped onto the ideal grid by a homography
+/// and every point must land near a distinct grid position. The grid
+/// starts at the corner nearest the image's top-left; rows run along the
+/// side holding `cols` points, and for a square grid the more horizontal
+/// one.
+fn order_grid(points: &[Point], cols: usize, rows: usize) -> Option<Vec<Point>> {
+    let outer = quad_corners(points)?;
+    let (c, r) = ((cols - 1) as f32, (rows - 1) as f32);
+    let ideal = [0.0, 0.0, c, 0.0, c, r, 0.0, r];
+    let flat: Vec<f32> = points.iter().flat_map(|p| [p.0 as f32, p.1 as f32]).collect();
+
+    let mut starts: Vec<usize> = (0..4).collect();
+    starts.sort_by(|&a, &b| (outer[a].0 + outer[a].1).total_cmp(&(outer[b].0 + outer[b].1)));
+    for start in starts {
+        let mut directions = [1, 3];
+        let along = |d: usize| {
+            let next = outer[(start + d) % 4];
+            (next.0 - outer[start].0).abs() - (next.1 - outer[start].1).abs()
+        };
+        if along(3) > along(1) {
+            directions.swap(0, 1);
+        }
+        for direction in directions {
+            let quad: Vec<f32> = (0..4)
+                .flat_map(|k| {
+                    let p = outer[(start + k * direction) % 4];
+                    [p.0 as f32, p.1 as f32]
+                })
+                .collect();
+            let matrix = find_homography(&quad, &ideal);
+            if matrix.is_empty() {
+                continue;
+            }
+
+            let mapped = perspective_transform_points(&flat, &matrix);
+            let mut grid: Vec<Option<Point>> = vec![None; cols * rows];
+            let fits = mapped.chunks_exact(2).zip(points).all(|(g, &p)| {
+                let (gx, gy) = (g[0].round(), g[1].round());
+                let residual = ((g[0] - gx).powi(2) + (g[1] - gy).powi(2)).sqrt() as f64;
+                if !(0.0..=c).contains(&gx) || !(0.0..=r).contains(&gy) || residual > MAX_GRID_RESIDUAL {
+                    return false;
+                }
+                let slot = &mut grid[gy as usize * cols + gx as usize];
+                slot.replace(p).is_none()
+            });
+            if fits {
+                return grid.into_iter().collect();
+            }
+        }
+    }
+    None
+}
+
+/// Sub-pixel corner refinement in the manner of OpenCV's `cornerSubPix`:
+/// within a `radius` window every image gradient is perpendicular to the
+/// line from the corner to that pixel, so the corner is the least-squares
+/// solution of `sum(g g^T) q = sum(g g^T p)`, iterated as the window moves.
+fn refine_corner(input: &[u8], width: usize, height: usize, corner: Point, radius: isize) -> Point {
+    let at = |x: isize, y: isize| input[y as usize * width + x as usize] as f64;
+    let mut q = corner;
+    for _ in 0..10 {
+        let (cx, cy) = (q.0.round() as isize, q.1.round() as isize);
+        let (mut a, mut b, mut c, mut bx, mut by) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        for y in (cy - radius).max(1)..=(cy + radius).min(height as isize - 2) {
+            for x in (cx - radius).max(1)..=(cx + radius).min(width as isize - 2) {
+                let gx = (at(x + 1, y) - at(x - 1, y)) / 2.0;
+                let gy = (at(x, y + 1) - at(x, y - 1)) / 2.0;
+                let (gxx, gxy, gyy) = (gx * gx, gx * gy, gy * gy);
+                a += gxx;
+                b += gxy;
+                c += gyy;
+                bx += gxx * x as f64 + gxy * y as f64;
+                by += gxy * x as f64 + gyy * y as f64;
+            }
+        }
+        let det = a * c - b * b;
+        if det.abs() < 1e-9 {
+            return q;
+        }
+        let next = ((c * bx - b * by) / det, (a * by - b * bx) / det);
+        // A solution far outside the window means there was no corner to find
+        if (next.0 - corner.0).abs() > radius as f64 || (next.1 - corner.1).abs() > radius as f64 {
+            return q;
+        }
+        let shift = (next.0 - q.0).abs() + (next.1 - q.1).abs();
+        q = next;
+        if shift < 0.01 {
+            break;
+        }
+    }
+    q
+}
+
+/// Finds the inner corners of a chessboard calibration pattern, where four
+/// squares meet, for lens calibration. `cols` and `rows` count inner
+/// corners (a board of 9 x 7 squares has 8 x 6), as OpenCV's
+/// `findChessboardCorners` pattern size does, and the board needs a light
+/// border around it.
+///
+/// The image is binarized (global Otsu, then a Bradley local threshold for
+/// uneven lighting), dark squares touching at their corners are separated
+/// by erosion (3, 5, then 7 pixels) and every quadrilateral component is
+/// kept. Corners of two squares that nearly meet give an inner corner. When
+/// exactly `cols * rows` are found and they fit a (perspective) grid, each
+/// is refined to sub-pixel accuracy on the image gradients.
+///
+/// # Returns
+/// Interleaved `[x0, y0, x1, y1, ...]` in row-major grid order: rows of
+/// `cols` corners (the more horizontal direction when `cols == rows`),
+/// starting at the grid corner nearest the top-left of the image, or
+/// `undefined` when the pattern is not found.
+#[wasm_bindgen]
+pub fn find_chessboard_corners(input: &[u8], width: usize, height: usize, cols: usize, rows: usize) -> Option<Vec<f32>> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if cols < 2 || rows < 2 {
+        panic!("Chessboard needs at least 2 x 2 inner corners");
+    }
+    if width < 3 || height < 3 {
+        return None;
+    }
+
+    let otsu = otsu_from_histogram(&histogram(input));
+    let global: Vec<u8> = input.iter().map(|&v| if v <= otsu { 255 } else { 0 }).collect();
+    let window = (width.max(height) / 4) | 1;
+    let local = bradley_threshold(input, width, height, window, 10.0, true);
+
+    for mask in [global, local] {
+        for separation in SEPARATIONS {
+            let squares = find_squares(&erode(&mask, width, height, separation), width, height);
+            let points = pair_corners(&squares, separation);
+            if points.len() != cols * rows {
+                continue;
+            }
+            let Some(grid) = order_grid(&points, cols, rows) else {
+                continue;
+            };
+
+            let side = squares.iter().map(|s| s.side).sum::<f64>() / squares.len() as f64;
+            let radius = ((side + separation as f64) / 4.0).clamp(2.0, 8.0) as isize;
+            return Some(
+                grid.into_iter()
+                    .flat_map(|p| {
+                        let (x, y) = refine_corner(input, width, height, p, radius);
+                        [x as f32, y as f32]
+                    })
+                    .collect(),
+            );
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_rotated_chessboard() {
+        // 7 x 5 squares of 16 pixels (6 x 4 inner corners), rotated by 12
+        // degrees about (100, 80), rendered with 4x4 supersampling
+        let (width, height, size) = (200, 160, 16.0);
+        let (sin, cos) = 12f64.to_radians().sin_cos();
+        let corner_at = |i: usize, j: usize| {
+            let (u, v) = ((i as f64 - 2.5) * size, (j as f64 - 1.5) * size);
+            (100.0 + u * cos - v * sin, 80.0 + u * sin + v * cos)
+        };
+        let input: Vec<u8> = (0..width * height)
+            .map(|idx| {
+                let mut sum = 0.0;
+                for s in 0..16 {
+                    let x = (idx % width) as f64 + (s % 4) as f64 / 4.0 - 0.375 - 100.0;
+                    let y = (idx / width) as f64 + (s / 4) as f64 / 4.0 - 0.375 - 80.0;
+                    let (u, v) = (x * cos + y * sin, -x * sin + y * cos);
+                    let (i, j) = ((u / size + 3.5).floor(), (v / size + 2.5).floor());
+                    let dark = (0.0..7.0).contains(&i) && (0.0..5.0).contains(&j) && (i + j) as i64 % 2 == 0;
+                    sum += if dark { 20.0 } else { 230.0 };
+                }
+                (sum / 16.0) as u8
+            })
+            .collect();
+
+        let corners = find_chessboard_corners(&input, width, height, 6, 4).expect("pattern not found");
+        assert_eq!(corners.len(), 48);
+        for j in 0..4 {
+            for i in 0..6 {
+                let (x, y) = corner_at(i, j);
+                let k = 2 * (j * 6 + i);
+                let error = ((corners[k] as f64 - x).powi(2) + (corners[k + 1] as f64 - y).powi(2)).sqrt();
+                assert!(error < 0.3, "corner ({}, {}) at {:?}, expected {:?}", i, j, &corners[k..k + 2], (x, y));
+            }
+        }
+
+        assert_eq!(find_chessboard_corners(&input, width, height, 5, 4), None);
+    }
+}
@@ -1,4 +1,11 @@
 use wasm_bindgen::prelude::*;
+use crate::rounding::RoundMode;
+use crate::histogram::{cdf_to_lut, clip_histogram};
+use crate::downscale::{area_bounds, bilinear_taps, nearest_index, SampleMode};
+use crate::roi::{check_roi, crop, paste};
+use crate::tiling::TileGrid;
+use crate::progress::{CancelCallback, Progress, ProgressCallback};
+use crate::statistics::histogram_entropy;
 
 /// CLAHE (Contrast Limited Adaptive Histogram Equalization)
 /// Based on Zuiderveld (1994)
@@ -11,38 +18,362 @@ pub fn clahe(
     tile_grid_y: usize,
     clip_limit: f32,
 ) -> Vec<u8> {
+    clahe_impl(input, width, height, tile_grid_x, tile_grid_y, clip_limit, &mut Progress::none())
+}
+
+/// `clahe` that calls `progress` with the completed fraction every
+/// `report_every_rows` rows (histogram and interpolation passes together),
+/// for progress bars on full-resolution scans.
+///
+/// ```js
+/// const out = clahe_with_progress(gray, w, h, 8, 8, 2.0, 64, (f) => bar.value = f);
+/// ```
+// Same leading arguments as `clahe`, plus the progress options
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn clahe_with_progress(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    tile_grid_x: usize,
+    tile_grid_y: usize,
+    clip_limit: f32,
+    report_every_rows: usize,
+    progress: Option<ProgressCallback>,
+) -> Vec<u8> {
+    // Both passes walk every row once
+    let mut progress = Progress::new(progress, 2 * height, report_every_rows);
+    clahe_impl(input, width, height, tile_grid_x, tile_grid_y, clip_limit, &mut progress)
+}
+
+/// `clahe_with_progress` that can be cancelled: `should_cancel` is polled
+/// together with each progress report, and a cancelled run returns an empty
+/// array. Either callback may be omitted.
+///
+/// ```js
+/// let stale = false;
+/// const out = clahe_cancellable(gray, w, h, 8, 8, 2.0, 64, undefined, () => stale);
+/// if (out.length === 0) { /* superseded by a newer capture */ }
+/// ```
+// Same leading arguments as `clahe`, plus the progress and cancel options
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn clahe_cancellable(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    tile_grid_x: usize,
+    tile_grid_y: usize,
+    clip_limit: f32,
+    check_every_rows: usize,
+    progress: Option<ProgressCallback>,
+    should_cancel: Option<CancelCallback>,
+) -> Vec<u8> {
+    let mut progress =
+        Progress::new(progress, 2 * height, check_every_rows).with_cancel(should_cancel);
+    clahe_impl(input, width, height, tile_grid_x, tile_grid_y, clip_limit, &mut progress)
+}
+
+fn clahe_impl(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    tile_grid_x: usize,
+    tile_grid_y: usize,
+    clip_limit: f32,
+    progress: &mut Progress,
+) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let grid = TileGrid::new(width, height, tile_grid_x, tile_grid_y);
+    clahe_with_limits(input, &grid, &vec![clip_limit; grid.num_tiles()], progress)
+}
+
+/// `clahe` over `grid` with a clip limit per tile, in tile order.
+fn clahe_with_limits(input: &[u8], grid: &TileGrid, clip_limits: &[f32], progress: &mut Progress) -> Vec<u8> {
+    let (width, height) = (grid.width, grid.height);
     let pixel_count = width * height;
-    if input.len() != pixel_count {
+    let tile_cdfs = compute_tile_cdfs(input, grid, clip_limits, progress);
+    if progress.is_cancelled() {
+        return Vec::new();
+    }
+
+    // Apply bilinear interpolation between tile CDFs for each pixel
+    let mut output = vec![0u8; pixel_count];
+    let columns = column_weights(grid, 0..width);
+    for y in 0..height {
+        let row_offset = y * width;
+        equalize_row(
+            &input[row_offset..row_offset + width],
+            y,
+            grid,
+            &tile_cdfs,
+            &columns,
+            &mut output[row_offset..row_offset + width],
+        );
+        progress.update(height + y + 1);
+        if progress.is_cancelled() {
+            return Vec::new();
+        }
+    }
+
+    output
+}
+
+/// `clahe` writing the result back into `input` instead of allocating an
+/// output image, to halve peak memory for full-resolution enhancement on
+/// low-end devices. Every pixel's mapping depends only on its own value and
+/// the tile tables, which are built before anything is overwritten, so the
+/// result equals `clahe`. Only one row of scratch space is used on top of
+/// the tables.
+#[wasm_bindgen]
+pub fn clahe_inplace(
+    input: &mut [u8],
+    width: usize,
+    height: usize,
+    tile_grid_x: usize,
+    tile_grid_y: usize,
+    clip_limit: f32,
+) {
+    if input.len() != width * height {
         panic!("Input array size doesn't match width * height");
     }
 
-    let tile_width = width / tile_grid_x;
-    let tile_height = height / tile_grid_y;
-    let tile_pixels = tile_width * tile_height;
+    let grid = TileGrid::new(width, height, tile_grid_x, tile_grid_y);
+    let columns = column_weights(&grid, 0..width);
+    clahe_inplace_with(input, &grid, &columns, clip_limit);
+}
+
+/// `clahe_inplace` with the grid and its `column_weights` supplied, so a
+/// batch of same-sized images computes them once.
+pub(crate) fn clahe_inplace_with(input: &mut [u8], grid: &TileGrid, columns: &[(usize, usize, f32)], clip_limit: f32) {
+    let width = grid.width;
+    let tile_cdfs = compute_tile_cdfs(input, grid, &vec![clip_limit; grid.num_tiles()], &mut Progress::none());
+    let mut row = vec![0u8; width];
+    for y in 0..grid.height {
+        let out_row = &mut input[y * width..(y + 1) * width];
+        row.copy_from_slice(out_row);
+        equalize_row(&row, y, grid, &tile_cdfs, columns, out_row);
+    }
+}
 
-    // Calculate the actual clip limit for histogram bins
-    let actual_clip = if clip_limit > 0.0 {
-        ((clip_limit * tile_pixels as f32) / 256.0).max(1.0) as u32
-    } else {
-        u32::MAX // No clipping
+/// `clahe` applied to the rectangle at (`roi_x`, `roi_y`) of size
+/// `roi_width` x `roi_height` only, e.g. the detected page area. The tile
+/// grid is laid over the ROI, so the ROI equals `clahe` on the cropped
+/// region and pixels outside it never influence the result.
+///
+/// # Returns
+/// Full-size image with the ROI equalized and every other pixel unchanged
+// The ROI rectangle is passed as plain numbers so JS needs no wrapper object
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn clahe_roi(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    roi_x: usize,
+    roi_y: usize,
+    roi_width: usize,
+    roi_height: usize,
+    tile_grid_x: usize,
+    tile_grid_y: usize,
+    clip_limit: f32,
+) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    check_roi(width, height, roi_x, roi_y, roi_width, roi_height);
+    let mut output = input.to_vec();
+    if roi_width == 0 || roi_height == 0 {
+        return output;
+    }
+
+    let region = crop(input, width, roi_x, roi_y, roi_width, roi_height);
+    let equalized = clahe(&region, roi_width, roi_height, tile_grid_x, tile_grid_y, clip_limit);
+    paste(&mut output, width, roi_x, roi_y, &equalized, roi_width, roi_height);
+    output
+}
+
+/// The per-tile mapping tables `clahe` interpolates between, for inspecting
+/// how each tile remaps intensities (e.g. plotting them to spot
+/// over-amplified tiles while tuning `clip_limit`). Same arguments as `clahe`.
+///
+/// # Returns
+/// `tile_grid_x * tile_grid_y * 256` bytes: one 256-entry LUT per tile,
+/// tiles in row-major order, entry `v` being the output for input `v`
+#[wasm_bindgen]
+pub fn clahe_tile_cdfs(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    tile_grid_x: usize,
+    tile_grid_y: usize,
+    clip_limit: f32,
+) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let grid = TileGrid::new(width, height, tile_grid_x, tile_grid_y);
+    compute_tile_cdfs(input, &grid, &vec![clip_limit; grid.num_tiles()], &mut Progress::none())
+}
+
+/// Per-tile clip limits `clahe_auto` picks for this image and tile grid,
+/// in row-major tile order. Each tile's limit falls with its histogram
+/// entropy, from 4.0 for a flat tile (0 bits) down to 1.0 for a tile using
+/// all 256 levels evenly (8 bits), so noisy tiles are clipped hardest and
+/// their noise is not amplified.
+#[wasm_bindgen]
+pub fn clahe_auto_clip_limits(input: &[u8], width: usize, height: usize, tile_grid_x: usize, tile_grid_y: usize) -> Vec<f32> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    auto_clip_limits(input, &TileGrid::new(width, height, tile_grid_x, tile_grid_y))
+}
+
+fn auto_clip_limits(input: &[u8], grid: &TileGrid) -> Vec<f32> {
+    grid.tiles()
+        .map(|(_, (x_start, y_start, x_end, y_end))| {
+            let mut hist = [0u32; 256];
+            for y in y_start..y_end {
+                for &v in &input[y * grid.width + x_start..y * grid.width + x_end] {
+                    hist[v as usize] += 1;
+                }
+            }
+            4.0 - 3.0 * (histogram_entropy(&hist) / 8.0) as f32
+        })
+        .collect()
+}
+
+/// `clahe` with each tile's clip limit chosen from the image itself (see
+/// `clahe_auto_clip_limits`), for one-call enhancement.
+#[wasm_bindgen]
+pub fn clahe_auto(input: &[u8], width: usize, height: usize, tile_grid_x: usize, tile_grid_y: usize) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let grid = TileGrid::new(width, height, tile_grid_x, tile_grid_y);
+    let clip_limits = auto_clip_limits(input, &grid);
+    clahe_with_limits(input, &grid, &clip_limits, &mut Progress::none())
+}
+
+/// `clahe` for a floating-point image with values in [0, 1], so a float
+/// pipeline needs no round trip through u8. Each tile's histogram has `bins`
+/// equal-width bins over [0, 1] (256 matches `clahe`; more keeps finer
+/// tonal detail on high-bit-depth captures) and is clipped as in `clahe`.
+/// The mapping interpolates the clipped CDF linearly within each bin, so
+/// output values are continuous rather than stepped at bin edges. Inputs
+/// outside [0, 1] are clamped; the output is in [0, 1].
+#[wasm_bindgen]
+pub fn clahe_f32(
+    input: &[f32],
+    width: usize,
+    height: usize,
+    tile_grid_x: usize,
+    tile_grid_y: usize,
+    clip_limit: f32,
+    bins: usize,
+) -> Vec<f32> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if bins == 0 {
+        panic!("Bin count must be greater than 0");
+    }
+
+    let grid = TileGrid::new(width, height, tile_grid_x, tile_grid_y);
+    let edges = bins + 1;
+    let bin_of = |v: f32| {
+        let pos = v.clamp(0.0, 1.0) * bins as f32;
+        let bin = (pos as usize).min(bins - 1);
+        (bin, pos - bin as f32)
     };
 
-    // Compute CDFs for each tile
-    let num_tiles = tile_grid_x * tile_grid_y;
+    // Per tile, the equalized value at each bin edge
+    let mut tile_maps = vec![0.0f32; grid.num_tiles() * edges];
+    let mut hist = vec![0u32; bins];
+    for (tile_idx, (x_start, y_start, x_end, y_end)) in grid.tiles() {
+        hist.fill(0);
+        for y in y_start..y_end {
+            for &v in &input[y * width + x_start..y * width + x_end] {
+                hist[bin_of(v).0] += 1;
+            }
+        }
+        let total = ((x_end - x_start) * (y_end - y_start)) as u32;
+        if clip_limit > 0.0 {
+            let limit = ((clip_limit * grid.tile_width as f32 * grid.tile_height as f32) / bins as f32).max(1.0);
+            clip_histogram(&mut hist, limit as u32);
+        }
+
+        // Same normalization as `cdf_to_lut`, evaluated at the bin tops
+        let cdf_min = hist.iter().copied().find(|&c| c > 0).unwrap_or(0) as f32;
+        let denom = total as f32 - cdf_min;
+        let map = &mut tile_maps[tile_idx * edges..(tile_idx + 1) * edges];
+        let mut running = 0u32;
+        for (k, m) in map.iter_mut().enumerate() {
+            *m = if denom > 0.0 {
+                ((running as f32 - cdf_min) / denom).max(0.0)
+            } else {
+                k as f32 / bins as f32
+            };
+            if k < bins {
+                running += hist[k];
+            }
+        }
+    }
+
+    let mut output = vec![0.0f32; width * height];
+    let columns: Vec<(usize, usize, f32)> = (0..width).map(|x| grid.x_weights(x)).collect();
+    for y in 0..height {
+        let (ty0, ty1, wy) = grid.y_weights(y);
+        for (x, &(tx0, tx1, wx)) in columns.iter().enumerate() {
+            let (bin, frac) = bin_of(input[y * width + x]);
+            let lookup = |ty: usize, tx: usize| {
+                let map = &tile_maps[(ty * grid.tiles_x + tx) * edges..];
+                map[bin] + frac * (map[bin + 1] - map[bin])
+            };
+            let top = lookup(ty0, tx0) * (1.0 - wx) + lookup(ty0, tx1) * wx;
+            let bottom = lookup(ty1, tx0) * (1.0 - wx) + lookup(ty1, tx1) * wx;
+            output[y * width + x] = top * (1.0 - wy) + bottom * wy;
+        }
+    }
+
+    output
+}
+
+/// Clipped-histogram CDF mapping table (256 entries) for every tile of
+/// `grid`, concatenated in tile order, each tile clipped at its entry of
+/// `clip_limits`. Reports progress after each row of tiles and stops early
+/// when `progress` is cancelled.
+fn compute_tile_cdfs(input: &[u8], grid: &TileGrid, clip_limits: &[f32], progress: &mut Progress) -> Vec<u8> {
+    let width = grid.width;
+    let tile_pixels = grid.tile_width * grid.tile_height;
+
     // Each tile has a 256-element CDF (stored as u8 mapped values)
-    let mut tile_cdfs = vec![0u8; num_tiles * 256];
+    let mut tile_cdfs = vec![0u8; grid.num_tiles() * 256];
 
-    for ty in 0..tile_grid_y {
-        for tx in 0..tile_grid_x {
-            let tile_idx = ty * tile_grid_x + tx;
+    for ty in 0..grid.tiles_y {
+        let mut rows_done = 0;
+        for tx in 0..grid.tiles_x {
+            let tile_idx = ty * grid.tiles_x + tx;
+
+            // Calculate the actual clip limit for histogram bins
+            let clip_limit = clip_limits[tile_idx];
+            let actual_clip = if clip_limit > 0.0 {
+                ((clip_limit * tile_pixels as f32) / 256.0).max(1.0) as u32
+            } else {
+                u32::MAX // No clipping
+            };
 
             // Compute histogram for this tile
             let mut hist = [0u32; 256];
-            let y_start = ty * tile_height;
-            let x_start = tx * tile_width;
-            let y_end = if ty == tile_grid_y - 1 { height } else { y_start + tile_height };
-            let x_end = if tx == tile_grid_x - 1 { width } else { x_start + tile_width };
+            let (x_start, y_start, x_end, y_end) = grid.tile_bounds(tx, ty);
             let actual_tile_pixels = (y_end - y_start) * (x_end - x_start);
+            rows_done = y_end;
 
             for y in y_start..y_end {
                 for x in x_start..x_end {
@@ -50,97 +381,79 @@ pub fn clahe(
                 }
             }
 
-            // Apply clip limit and redistribute excess
             if actual_clip < u32::MAX {
-                let mut excess = 0u32;
-                for bin in hist.iter_mut() {
-                    if *bin > actual_clip {
-                        excess += *bin - actual_clip;
-                        *bin = actual_clip;
-                    }
-                }
-                // Redistribute excess equally
-                let per_bin = excess / 256;
-                let remainder = (excess % 256) as usize;
-                for (i, bin) in hist.iter_mut().enumerate() {
-                    *bin += per_bin;
-                    if i < remainder {
-                        *bin += 1;
-                    }
-                }
-            }
-
-            // Compute CDF and map to [0, 255]
-            let mut cdf = [0u32; 256];
-            cdf[0] = hist[0];
-            for i in 1..256 {
-                cdf[i] = cdf[i - 1] + hist[i];
-            }
-
-            // Find min CDF value (first non-zero)
-            let cdf_min = cdf.iter().copied().find(|&v| v > 0).unwrap_or(0);
-            let denom = actual_tile_pixels as f32 - cdf_min as f32;
-
-            let cdf_slice = &mut tile_cdfs[tile_idx * 256..(tile_idx + 1) * 256];
-            if denom > 0.0 {
-                for i in 0..256 {
-                    let val = ((cdf[i] as f32 - cdf_min as f32) / denom * 255.0).round();
-                    cdf_slice[i] = val.clamp(0.0, 255.0) as u8;
-                }
-            } else {
-                for i in 0..256 {
-                    cdf_slice[i] = i as u8;
-                }
+                clip_histogram(&mut hist, actual_clip);
             }
+            let lut = cdf_to_lut(&hist, actual_tile_pixels as u32);
+            tile_cdfs[tile_idx * 256..(tile_idx + 1) * 256].copy_from_slice(&lut);
+        }
+        progress.update(rows_done);
+        if progress.is_cancelled() {
+            break;
         }
     }
 
-    // Apply bilinear interpolation between tile CDFs for each pixel
-    let mut output = vec![0u8; pixel_count];
+    tile_cdfs
+}
 
-    for y in 0..height {
-        for x in 0..width {
-            let pixel_val = input[y * width + x] as usize;
-
-            // Find the tile coordinates (floating point)
-            // Map pixel position to tile center coordinates
-            let fy = (y as f32 / tile_height as f32) - 0.5;
-            let fx = (x as f32 / tile_width as f32) - 0.5;
-
-            // Clamp to valid tile range
-            let fy_clamped = fy.clamp(0.0, (tile_grid_y - 1) as f32);
-            let fx_clamped = fx.clamp(0.0, (tile_grid_x - 1) as f32);
-
-            let ty0 = fy_clamped.floor() as usize;
-            let tx0 = fx_clamped.floor() as usize;
-            let ty1 = (ty0 + 1).min(tile_grid_y - 1);
-            let tx1 = (tx0 + 1).min(tile_grid_x - 1);
-
-            let wy = fy_clamped - ty0 as f32;
-            let wx = fx_clamped - tx0 as f32;
-
-            // Get CDF values from 4 neighboring tiles
-            let v00 = tile_cdfs[(ty0 * tile_grid_x + tx0) * 256 + pixel_val] as f32;
-            let v10 = tile_cdfs[(ty0 * tile_grid_x + tx1) * 256 + pixel_val] as f32;
-            let v01 = tile_cdfs[(ty1 * tile_grid_x + tx0) * 256 + pixel_val] as f32;
-            let v11 = tile_cdfs[(ty1 * tile_grid_x + tx1) * 256 + pixel_val] as f32;
-
-            // Bilinear interpolation
-            let top = v00 * (1.0 - wx) + v10 * wx;
-            let bottom = v01 * (1.0 - wx) + v11 * wx;
-            let result = top * (1.0 - wy) + bottom * wy;
-
-            output[y * width + x] = result.round().clamp(0.0, 255.0) as u8;
-        }
-    }
+/// Neighbouring tile columns (as offsets into a row of tile CDFs) and the
+/// blend weight of the right one, for the given pixel columns. These are the
+/// same for every row, so they are computed once per image.
+pub(crate) fn column_weights(grid: &TileGrid, xs: impl Iterator<Item = usize>) -> Vec<(usize, usize, f32)> {
+    xs.map(|x| {
+        let (tx0, tx1, wx) = grid.x_weights(x);
+        (tx0 * 256, tx1 * 256, wx)
+    })
+    .collect()
+}
 
-    output
+/// Equalizes source row `y` by bilinear interpolation between the CDFs of
+/// the four surrounding tiles. `columns` holds the `column_weights` of the
+/// pixels in `row`.
+pub(crate) fn equalize_row(
+    row: &[u8],
+    y: usize,
+    grid: &TileGrid,
+    tile_cdfs: &[u8],
+    columns: &[(usize, usize, f32)],
+    out_row: &mut [u8],
+) {
+    let (ty0, ty1, wy) = grid.y_weights(y);
+    let rounding = RoundMode::current();
+
+    let tile_row_len = grid.tiles_x * 256;
+    let top_cdfs = &tile_cdfs[ty0 * tile_row_len..(ty0 + 1) * tile_row_len];
+    let bottom_cdfs = &tile_cdfs[ty1 * tile_row_len..(ty1 + 1) * tile_row_len];
+
+    for ((out, &pixel), &(col0, col1, wx)) in out_row.iter_mut().zip(row).zip(columns) {
+        let pixel_val = pixel as usize;
+
+        // Get CDF values from 4 neighboring tiles
+        let v00 = top_cdfs[col0 + pixel_val] as f32;
+        let v10 = top_cdfs[col1 + pixel_val] as f32;
+        let v01 = bottom_cdfs[col0 + pixel_val] as f32;
+        let v11 = bottom_cdfs[col1 + pixel_val] as f32;
+
+        // Bilinear interpolation
+        let top = v00 * (1.0 - wx) + v10 * wx;
+        let bottom = v01 * (1.0 - wx) + v11 * wx;
+        let result = top * (1.0 - wy) + bottom * wy;
+
+        *out = rounding.to_u8(result);
+    }
 }
 
-/// Fused CLAHE + bilinear downscale in a single pass.
-/// Computes tile CDFs at full resolution, then for each output pixel
-/// maps back to source coordinates and applies CLAHE interpolation.
-/// Memory: input (W*H) + tile_cdfs (num_tiles*256) + output (tw*th) — no full-res intermediate.
+/// Fused CLAHE + downscale in a single pass.
+/// Computes tile CDFs at full resolution, then samples the CLAHE-equalized
+/// source image at each output pixel according to `sampling`. Only the
+/// source pixels that are actually sampled get equalized.
+///
+/// `SampleMode::Area` matches `clahe` followed by an area downscale without
+/// aliasing; `Nearest`, used when `sampling` is omitted, is the cheapest,
+/// touching one source pixel per output.
+/// Memory: input (W*H) + tile_cdfs (num_tiles*256) + a few rows + output (tw*th) — no full-res intermediate.
+// Exported to JS, where a positional argument list is the calling convention
+#[allow(clippy::too_many_arguments)]
 #[wasm_bindgen]
 pub fn clahe_and_downscale(
     input: &[u8],
@@ -151,6 +464,7 @@ pub fn clahe_and_downscale(
     tile_grid_x: usize,
     tile_grid_y: usize,
     clip_limit: f32,
+    sampling: Option<SampleMode>,
 ) -> Vec<u8> {
     let pixel_count = width * height;
     if input.len() != pixel_count {
@@ -162,122 +476,159 @@ pub fn clahe_and_downscale(
         return clahe(input, width, height, tile_grid_x, tile_grid_y, clip_limit);
     }
 
-    let tile_width = width / tile_grid_x;
-    let tile_height = height / tile_grid_y;
-    let tile_pixels = tile_width * tile_height;
-
-    let actual_clip = if clip_limit > 0.0 {
-        ((clip_limit * tile_pixels as f32) / 256.0).max(1.0) as u32
-    } else {
-        u32::MAX
-    };
-
     // Compute CDFs for each tile (same as regular CLAHE)
-    let num_tiles = tile_grid_x * tile_grid_y;
-    let mut tile_cdfs = vec![0u8; num_tiles * 256];
-
-    for ty in 0..tile_grid_y {
-        for tx in 0..tile_grid_x {
-            let tile_idx = ty * tile_grid_x + tx;
-
-            let mut hist = [0u32; 256];
-            let y_start = ty * tile_height;
-            let x_start = tx * tile_width;
-            let y_end = if ty == tile_grid_y - 1 { height } else { y_start + tile_height };
-            let x_end = if tx == tile_grid_x - 1 { width } else { x_start + tile_width };
-            let actual_tile_pixels = (y_end - y_start) * (x_end - x_start);
+    let grid = TileGrid::new(width, height, tile_grid_x, tile_grid_y);
+    let tile_cdfs = compute_tile_cdfs(input, &grid, &vec![clip_limit; grid.num_tiles()], &mut Progress::none());
 
-            for y in y_start..y_end {
-                for x in x_start..x_end {
-                    hist[input[y * width + x] as usize] += 1;
+    let out_pixels = target_width * target_height;
+    let mut output = vec![0u8; out_pixels];
+    let columns = column_weights(&grid, 0..width);
+
+    match sampling.unwrap_or(SampleMode::Nearest) {
+        SampleMode::Nearest => {
+            // Equalize only the sampled pixels: gather them into a row
+            // together with the tile weights of their source columns
+            let xs: Vec<usize> = (0..target_width).map(|ox| nearest_index(ox, width, target_width)).collect();
+            let sampled_columns: Vec<(usize, usize, f32)> = xs.iter().map(|&x| columns[x]).collect();
+            let mut gathered = vec![0u8; target_width];
+            for oy in 0..target_height {
+                let y = nearest_index(oy, height, target_height);
+                let row = &input[y * width..(y + 1) * width];
+                for (g, &x) in gathered.iter_mut().zip(&xs) {
+                    *g = row[x];
                 }
+                let out_row = &mut output[oy * target_width..(oy + 1) * target_width];
+                equalize_row(&gathered, y, &grid, &tile_cdfs, &sampled_columns, out_row);
             }
-
-            if actual_clip < u32::MAX {
-                let mut excess = 0u32;
-                for bin in hist.iter_mut() {
-                    if *bin > actual_clip {
-                        excess += *bin - actual_clip;
-                        *bin = actual_clip;
+        }
+        SampleMode::Bilinear => {
+            // Equalize the four source neighbours of every output pixel, then blend
+            let taps: Vec<(usize, usize, f32)> =
+                (0..target_width).map(|ox| bilinear_taps(ox, width, target_width)).collect();
+            let left_columns: Vec<(usize, usize, f32)> = taps.iter().map(|t| columns[t.0]).collect();
+            let right_columns: Vec<(usize, usize, f32)> = taps.iter().map(|t| columns[t.1]).collect();
+            let mut gathered = vec![0u8; target_width];
+            let rounding = RoundMode::current();
+            let mut corners = [vec![0u8; target_width], vec![0u8; target_width], vec![0u8; target_width], vec![0u8; target_width]];
+            for oy in 0..target_height {
+                let (y0, y1, fy) = bilinear_taps(oy, height, target_height);
+                for (i, &y) in [y0, y1].iter().enumerate() {
+                    let row = &input[y * width..(y + 1) * width];
+                    for (g, t) in gathered.iter_mut().zip(&taps) {
+                        *g = row[t.0];
                     }
-                }
-                let per_bin = excess / 256;
-                let remainder = (excess % 256) as usize;
-                for (i, bin) in hist.iter_mut().enumerate() {
-                    *bin += per_bin;
-                    if i < remainder {
-                        *bin += 1;
+                    equalize_row(&gathered, y, &grid, &tile_cdfs, &left_columns, &mut corners[2 * i]);
+                    for (g, t) in gathered.iter_mut().zip(&taps) {
+                        *g = row[t.1];
                     }
+                    equalize_row(&gathered, y, &grid, &tile_cdfs, &right_columns, &mut corners[2 * i + 1]);
+                }
+                for (ox, &(_, _, fx)) in taps.iter().enumerate() {
+                    let top = corners[0][ox] as f32 * (1.0 - fx) + corners[1][ox] as f32 * fx;
+                    let bottom = corners[2][ox] as f32 * (1.0 - fx) + corners[3][ox] as f32 * fx;
+                    let result = top * (1.0 - fy) + bottom * fy;
+                    output[oy * target_width + ox] = rounding.to_u8(result);
                 }
             }
-
-            let mut cdf = [0u32; 256];
-            cdf[0] = hist[0];
-            for i in 1..256 {
-                cdf[i] = cdf[i - 1] + hist[i];
-            }
-
-            let cdf_min = cdf.iter().copied().find(|&v| v > 0).unwrap_or(0);
-            let denom = actual_tile_pixels as f32 - cdf_min as f32;
-
-            let cdf_slice = &mut tile_cdfs[tile_idx * 256..(tile_idx + 1) * 256];
-            if denom > 0.0 {
-                for i in 0..256 {
-                    let val = ((cdf[i] as f32 - cdf_min as f32) / denom * 255.0).round();
-                    cdf_slice[i] = val.clamp(0.0, 255.0) as u8;
+        }
+        SampleMode::Area => {
+            // Source rows are equalized one at a time and summed per column,
+            // so no full-resolution intermediate is needed
+            let rounding = RoundMode::current();
+            let mut equalized = vec![0u8; width];
+            let mut column_sums = vec![0u32; width];
+            for oy in 0..target_height {
+                let (y0, y1) = area_bounds(oy, height, target_height);
+                column_sums.fill(0);
+                for y in y0..y1 {
+                    let row = &input[y * width..(y + 1) * width];
+                    equalize_row(row, y, &grid, &tile_cdfs, &columns, &mut equalized);
+                    for (sum, &v) in column_sums.iter_mut().zip(&equalized) {
+                        *sum += v as u32;
+                    }
                 }
-            } else {
-                for i in 0..256 {
-                    cdf_slice[i] = i as u8;
+
+                for ox in 0..target_width {
+                    let (x0, x1) = area_bounds(ox, width, target_width);
+                    let count = ((x1 - x0) * (y1 - y0)) as u32;
+                    let sum: u32 = column_sums[x0..x1].iter().sum();
+                    output[oy * target_width + ox] = rounding.to_u8(sum as f32 / count as f32);
                 }
             }
         }
     }
 
-    // Now produce downscaled output using bilinear mapping + CLAHE interpolation
-    let out_pixels = target_width * target_height;
-    let mut output = vec![0u8; out_pixels];
-
-    let sx = width as f64 / target_width as f64;
-    let sy = height as f64 / target_height as f64;
-
-    for oy in 0..target_height {
-        let src_y = (oy as f64 + 0.5) * sy - 0.5;
-        let src_y_round = src_y.round().clamp(0.0, (height - 1) as f64) as usize;
-
-        for ox in 0..target_width {
-            let src_x = (ox as f64 + 0.5) * sx - 0.5;
-            let src_x_round = src_x.round().clamp(0.0, (width - 1) as f64) as usize;
-
-            let pixel_val = input[src_y_round * width + src_x_round] as usize;
-
-            // Apply CLAHE bilinear interpolation using tile CDFs
-            let fy = (src_y_round as f32 / tile_height as f32) - 0.5;
-            let fx = (src_x_round as f32 / tile_width as f32) - 0.5;
-
-            let fy_clamped = fy.clamp(0.0, (tile_grid_y - 1) as f32);
-            let fx_clamped = fx.clamp(0.0, (tile_grid_x - 1) as f32);
+    output
+}
 
-            let ty0 = fy_clamped.floor() as usize;
-            let tx0 = fx_clamped.floor() as usize;
-            let ty1 = (ty0 + 1).min(tile_grid_y - 1);
-            let tx1 = (tx0 + 1).min(tile_grid_x - 1);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::histogram::histogram;
+    use crate::tiled::ClaheTiled;
+
+    #[test]
+    fn test_single_tile_matches_cdf_to_lut() {
+        // With one tile and no clipping, the interpolation collapses to the
+        // tile's mapping, so both CLAHE implementations must equal the helper
+        let (width, height) = (23, 17);
+        let input: Vec<u8> = (0..width * height).map(|i| ((i * 37) % 97 + 40) as u8).collect();
+        let lut = cdf_to_lut(&histogram(&input), (width * height) as u32);
+        let expected: Vec<u8> = input.iter().map(|&v| lut[v as usize]).collect();
+
+        assert_eq!(clahe(&input, width, height, 1, 1, 0.0), expected);
+        assert_eq!(clahe_tile_cdfs(&input, width, height, 1, 1, 0.0), lut.to_vec());
+
+        let mut in_place = input.clone();
+        clahe_inplace(&mut in_place, width, height, 1, 1, 0.0);
+        assert_eq!(in_place, expected);
+
+        let mut tiled = ClaheTiled::new(width, height, 1, 1, 0.0);
+        tiled.accumulate(&input, 0, 0, width, height);
+        assert_eq!(tiled.apply(&input, 0, 0, width, height), expected);
+
+        // Flat input keeps its values
+        let flat = vec![77u8; 16];
+        assert_eq!(cdf_to_lut(&histogram(&flat), 16)[77], 77);
+    }
 
-            let wy = fy_clamped - ty0 as f32;
-            let wx = fx_clamped - tx0 as f32;
+    #[test]
+    fn test_clahe_f32_matches_clahe_at_bin_tops() {
+        // A float value just below the top of bin v maps to the u8 table entry
+        let (width, height) = (40, 30);
+        let input: Vec<u8> = (0..width * height).map(|i| ((i * 37 + i / width * 5) % 180 + 30) as u8).collect();
+        let as_float: Vec<f32> = input.iter().map(|&v| (v as f32 + 0.999) / 256.0).collect();
+
+        let expected = clahe(&input, width, height, 3, 2, 2.0);
+        let actual = clahe_f32(&as_float, width, height, 3, 2, 2.0, 256);
+        // The u8 path rounds both its tables and the blended result
+        for (&a, &e) in actual.iter().zip(&expected) {
+            assert!((a * 255.0 - e as f32).abs() <= 1.0, "{} vs {}", a * 255.0, e);
+        }
+    }
 
-            let v00 = tile_cdfs[(ty0 * tile_grid_x + tx0) * 256 + pixel_val] as f32;
-            let v10 = tile_cdfs[(ty0 * tile_grid_x + tx1) * 256 + pixel_val] as f32;
-            let v01 = tile_cdfs[(ty1 * tile_grid_x + tx0) * 256 + pixel_val] as f32;
-            let v11 = tile_cdfs[(ty1 * tile_grid_x + tx1) * 256 + pixel_val] as f32;
+    #[test]
+    fn test_auto_clip_limit_lower_for_noisy_tile() {
+        // Left tile: two clean levels; right tile: pseudo-random noise
+        let (width, height) = (64, 32);
+        let input: Vec<u8> = (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                if x < 32 {
+                    if y < 16 { 60 } else { 190 }
+                } else {
+                    ((i * 7919 + i * i * 31) % 251) as u8
+                }
+            })
+            .collect();
 
-            let top = v00 * (1.0 - wx) + v10 * wx;
-            let bottom = v01 * (1.0 - wx) + v11 * wx;
-            let result = top * (1.0 - wy) + bottom * wy;
+        let limits = clahe_auto_clip_limits(&input, width, height, 2, 1);
+        assert_eq!(limits.len(), 2);
+        assert!(limits[1] < limits[0], "{:?}", limits);
+        assert!(limits.iter().all(|&l| (1.0..=4.0).contains(&l)));
 
-            output[oy * target_width + ox] = result.round().clamp(0.0, 255.0) as u8;
-        }
+        let grid = TileGrid::new(width, height, 2, 1);
+        let expected = clahe_with_limits(&input, &grid, &limits, &mut Progress::none());
+        assert_eq!(clahe_auto(&input, width, height, 2, 1), expected);
     }
-
-    output
 }
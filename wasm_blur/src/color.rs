@@ -0,0 +1,218 @@
+use wasm_bindgen::prelude::*;
+use crate::clahe::clahe;
+use crate::dilation::dilate;
+use crate::gaussian_blur::blur;
+use crate::morphology::erode;
+use crate::rounding::RoundMode;
+
+/// Luminance formula used by every entry point that turns RGBA into a
+/// single gray channel (`rgba_to_gray`, `canny_rgba`, ...), so they all agree.
+/// Other weights go through `rgba_to_gray_weighted`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Luma {
+    /// `0.299 R + 0.587 G + 0.114 B` (BT.601, OpenCV's `cvtColor`)
+    Rec601,
+    /// `0.2126 R + 0.7152 G + 0.0722 B` (BT.709 / sRGB)
+    Rec709,
+    /// `(R + G + B) / 3`
+    Average,
+}
+
+impl Luma {
+    /// `[r, g, b]` weights, summing to 1.
+    pub(crate) fn weights(self) -> [f32; 3] {
+        match self {
+            Luma::Rec601 => [0.299, 0.587, 0.114],
+            Luma::Rec709 => [0.2126, 0.7152, 0.0722],
+            Luma::Average => [1.0 / 3.0; 3],
+        }
+    }
+}
+
+/// Gray image from RGBA with the `luma` weights; alpha is ignored.
+#[wasm_bindgen]
+pub fn rgba_to_gray(input: &[u8], width: usize, height: usize, luma: Luma) -> Vec<u8> {
+    gray_with_weights(input, width, height, luma.weights())
+}
+
+/// `rgba_to_gray` with explicit `r`, `g` and `b` weights, e.g. to match a
+/// specific color pipeline. They are normalized to sum to 1.
+#[wasm_bindgen]
+pub fn rgba_to_gray_weighted(input: &[u8], width: usize, height: usize, r: f32, g: f32, b: f32) -> Vec<u8> {
+    let total = r + g + b;
+    if !(r >= 0.0 && g >= 0.0 && b >= 0.0 && total > 0.0 && total.is_finite()) {
+        panic!("Luma weights must be non-negative with a positive sum");
+    }
+    gray_with_weights(input, width, height, [r / total, g / total, b / total])
+}
+
+fn gray_with_weights(input: &[u8], width: usize, height: usize, [wr, wg, wb]: [f32; 3]) -> Vec<u8> {
+    if input.len() != width * height * 4 {
+        panic!("Input array size doesn't match width * height * 4");
+    }
+
+    let rounding = RoundMode::current();
+    input
+        .chunks_exact(4)
+        .map(|px| rounding.to_u8(wr * px[0] as f32 + wg * px[1] as f32 + wb * px[2] as f32))
+        .collect()
+}
+
+/// Runs a single-channel filter independently on R, G and B of an RGBA
+/// image and interleaves the results again; alpha is copied unchanged.
+/// `filter` receives each channel as a tightly packed `width * height`
+/// plane and must return one of the same size.
+pub(crate) fn apply_per_channel(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    filter: impl Fn(&[u8]) -> Vec<u8>,
+) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count * 4 {
+        panic!("Input array size doesn't match width * height * 4");
+    }
+
+    let mut output = input.to_vec();
+    let mut plane = vec![0u8; pixel_count];
+    for c in 0..3 {
+        for (p, px) in plane.iter_mut().zip(input.chunks_exact(4)) {
+            *p = px[c];
+        }
+        let filtered = filter(&plane);
+        for (px, &v) in output.chunks_exact_mut(4).zip(filtered.iter()) {
+            px[c] = v;
+        }
+    }
+
+    output
+}
+
+/// `blur` applied to R, G and B separately; alpha is copied unchanged.
+#[wasm_bindgen]
+pub fn blur_rgba(input: &[u8], width: usize, height: usize, kernel_size: usize, sigma: f32) -> Vec<u8> {
+    apply_per_channel(input, width, height, |plane| blur(plane, width, height, kernel_size, sigma))
+}
+
+/// `erode` applied to R, G and B separately; alpha is copied unchanged.
+#[wasm_bindgen]
+pub fn erode_rgba(input: &[u8], width: usize, height: usize, kernel_size: usize) -> Vec<u8> {
+    apply_per_channel(input, width, height, |plane| erode(plane, width, height, kernel_size))
+}
+
+/// `dilate` applied to R, G and B separately; alpha is copied unchanged.
+#[wasm_bindgen]
+pub fn dilate_rgba(input: &[u8], width: usize, height: usize, kernel_size: usize) -> Vec<u8> {
+    apply_per_channel(input, width, height, |plane| dilate(plane, width, height, kernel_size))
+}
+
+/// `clahe` applied to R, G and B separately; alpha is copied unchanged.
+/// Equalizing the channels independently can shift hues where they differ
+/// strongly in contrast, which suits faded scans more than color photos. No
+/// luminance is involved; to equalize brightness only, run `clahe` on the Y
+/// plane from `rgba_to_ycbcr`.
+#[wasm_bindgen]
+pub fn clahe_rgba(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    tile_grid_x: usize,
+    tile_grid_y: usize,
+    clip_limit: f32,
+) -> Vec<u8> {
+    apply_per_channel(input, width, height, |plane| {
+        clahe(plane, width, height, tile_grid_x, tile_grid_y, clip_limit)
+    })
+}
+
+/// Full-range BT.601 YCbCr (the JPEG / JFIF variant) of an RGBA image:
+/// `Y = 0.299 R + 0.587 G + 0.114 B`, with Cb and Cr centered on 128 and
+/// using the whole 0-255 range. Alpha is dropped.
+///
+/// # Arguments
+/// * `planar` - false for interleaved `Y Cb Cr Y Cb Cr ...`, true for a full
+///   `width * height` Y plane followed by the Cb plane and then the Cr plane,
+///   so `output[..width * height]` can go straight into `clahe` and friends
+///
+/// # Returns
+/// `width * height * 3` bytes in the chosen layout
+#[wasm_bindgen]
+pub fn rgba_to_ycbcr(input: &[u8], width: usize, height: usize, planar: bool) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count * 4 {
+        panic!("Input array size doesn't match width * height * 4");
+    }
+
+    let rounding = RoundMode::current();
+    let mut output = vec![0u8; pixel_count * 3];
+    for (i, px) in input.chunks_exact(4).enumerate() {
+        let (r, g, b) = (px[0] as f32, px[1] as f32, px[2] as f32);
+        let y = 0.299 * r + 0.587 * g + 0.114 * b;
+        let cb = 128.0 - 0.168_736 * r - 0.331_264 * g + 0.5 * b;
+        let cr = 128.0 + 0.5 * r - 0.418_688 * g - 0.081_312 * b;
+        for (c, v) in [y, cb, cr].into_iter().enumerate() {
+            let idx = if planar { c * pixel_count + i } else { i * 3 + c };
+            output[idx] = rounding.to_u8(v);
+        }
+    }
+
+    output
+}
+
+/// Inverse of `rgba_to_ycbcr`, reading the same `planar` or interleaved
+/// layout. Alpha is set to 255.
+#[wasm_bindgen]
+pub fn ycbcr_to_rgba(input: &[u8], width: usize, height: usize, planar: bool) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count * 3 {
+        panic!("Input array size doesn't match width * height * 3");
+    }
+
+    let rounding = RoundMode::current();
+    let mut output = vec![255u8; pixel_count * 4];
+    for (i, px) in output.chunks_exact_mut(4).enumerate() {
+        let sample = |c: usize| input[if planar { c * pixel_count + i } else { i * 3 + c }] as f32;
+        let (y, cb, cr) = (sample(0), sample(1) - 128.0, sample(2) - 128.0);
+        px[0] = rounding.to_u8(y + 1.402 * cr);
+        px[1] = rounding.to_u8(y - 0.344_136 * cb - 0.714_136 * cr);
+        px[2] = rounding.to_u8(y + 1.772 * cb);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ycbcr_round_trip() {
+        let input = [255, 0, 0, 255, 12, 200, 90, 40, 128, 128, 128, 0, 0, 0, 0, 255];
+        for planar in [false, true] {
+            let ycbcr = rgba_to_ycbcr(&input, 2, 2, planar);
+            let rgba = ycbcr_to_rgba(&ycbcr, 2, 2, planar);
+            for (i, (&a, &b)) in input.iter().zip(&rgba).enumerate() {
+                if i % 4 != 3 {
+                    assert!(a.abs_diff(b) <= 2, "channel {} differs: {} vs {}", i, a, b);
+                }
+            }
+        }
+        // Grey stays neutral
+        assert_eq!(rgba_to_ycbcr(&[128, 128, 128, 255], 1, 1, false), vec![128, 128, 128]);
+    }
+
+    #[test]
+    fn test_luma_weights_sum_to_one() {
+        for luma in [Luma::Rec601, Luma::Rec709, Luma::Average] {
+            let total: f32 = luma.weights().iter().sum();
+            assert!((total - 1.0).abs() < 1e-6);
+        }
+        // BT.601 luma agrees with the Y of rgba_to_ycbcr
+        let input = [12, 200, 90, 255, 250, 30, 180, 0];
+        let y = rgba_to_ycbcr(&input, 2, 1, true);
+        assert_eq!(rgba_to_gray(&input, 2, 1, Luma::Rec601), y[..2].to_vec());
+        // Explicit weights are normalized
+        assert_eq!(rgba_to_gray_weighted(&input, 2, 1, 2.99, 5.87, 1.14), y[..2].to_vec());
+    }
+}
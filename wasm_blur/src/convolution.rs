@@ -0,0 +1,119 @@
+use wasm_bindgen::prelude::*;
+use crate::border::{resolve, BorderMode};
+use crate::rounding::RoundMode;
+
+/// Separable convolution with two arbitrary odd-length 1D kernels.
+/// `kernel_x` is applied along rows, then `kernel_y` along columns, with
+/// clamped (replicated) borders. The intermediate pass is kept in f32 so
+/// negative weights (derivative filters) are not truncated between passes;
+/// only the final result is rounded and clamped to [0, 255].
+///
+/// Kernels are used as given; normalize them beforehand for smoothing filters.
+#[wasm_bindgen]
+pub fn convolve_separable(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    kernel_x: &[f32],
+    kernel_y: &[f32],
+) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+    if kernel_x.len().is_multiple_of(2) || kernel_y.len().is_multiple_of(2) {
+        panic!("Kernel lengths must be odd");
+    }
+
+    let half_x = (kernel_x.len() / 2) as isize;
+    let half_y = (kernel_y.len() / 2) as isize;
+
+    // Horizontal pass
+    let mut temp = vec![0.0f32; pixel_count];
+    for y in 0..height {
+        let row = &input[y * width..(y + 1) * width];
+        for x in 0..width {
+            let mut sum = 0.0f32;
+            for (k, &weight) in kernel_x.iter().enumerate() {
+                let nx = (x as isize + k as isize - half_x).clamp(0, (width - 1) as isize) as usize;
+                sum += row[nx] as f32 * weight;
+            }
+            temp[y * width + x] = sum;
+        }
+    }
+
+    // Vertical pass
+    let rounding = RoundMode::current();
+    let mut output = vec![0u8; pixel_count];
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = 0.0f32;
+            for (k, &weight) in kernel_y.iter().enumerate() {
+                let ny = (y as isize + k as isize - half_y).clamp(0, (height - 1) as isize) as usize;
+                sum += temp[ny * width + x] * weight;
+            }
+            output[y * width + x] = rounding.to_u8(sum);
+        }
+    }
+
+    output
+}
+
+/// General (non-separable) 2D convolution with a `kernel_width` x
+/// `kernel_height` kernel in row-major order. As in OpenCV's `filter2D` the
+/// kernel is applied as a correlation (not flipped) and anchored at its center,
+/// so both dimensions must be odd.
+///
+/// `bias` is added before rounding and clamping to [0, 255]; use 128 for
+/// bipolar kernels such as emboss or Laplacian so negative responses stay visible.
+// Kernel dimensions, bias and border are all needed and JS passes them positionally
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn convolve_2d(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    kernel: &[f32],
+    kernel_width: usize,
+    kernel_height: usize,
+    bias: f32,
+    border: BorderMode,
+) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+    if kernel.len() != kernel_width * kernel_height {
+        panic!("Kernel size doesn't match kernel_width * kernel_height");
+    }
+    if kernel_width.is_multiple_of(2) || kernel_height.is_multiple_of(2) {
+        panic!("Kernel dimensions must be odd");
+    }
+
+    let half_w = (kernel_width / 2) as isize;
+    let half_h = (kernel_height / 2) as isize;
+    let rounding = RoundMode::current();
+    let mut output = vec![0u8; pixel_count];
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = bias;
+            for ky in 0..kernel_height {
+                let ny = match resolve(y as isize + ky as isize - half_h, height, border) {
+                    Some(ny) => ny,
+                    None => continue,
+                };
+                let row = &input[ny * width..(ny + 1) * width];
+                let krow = &kernel[ky * kernel_width..(ky + 1) * kernel_width];
+                for (kx, &weight) in krow.iter().enumerate() {
+                    if let Some(nx) = resolve(x as isize + kx as isize - half_w, width, border) {
+                        sum += row[nx] as f32 * weight;
+                    }
+                }
+            }
+            output[y * width + x] = rounding.to_u8(sum);
+        }
+    }
+
+    output
+}
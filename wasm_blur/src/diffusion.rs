@@ -0,0 +1,84 @@
+use wasm_bindgen::prelude::*;
+use crate::gradient_calculation::calculate_gradient_magnitude;
+use crate::rounding::RoundMode;
+
+/// Perona-Malik anisotropic diffusion: smooths within regions while
+/// stopping at edges, which keeps line art crisp where a Gaussian would
+/// blur it.
+///
+/// Every iteration moves each pixel towards its four neighbours by
+/// `lambda * sum(c * (I_n - I))`. The conduction `c = 1 / (1 + (|grad| / kappa)^2)`
+/// is taken from the Sobel gradient magnitude of the current image (scaled
+/// to grey levels per pixel, so a step of height `h` reads as about `h / 2`)
+/// and averaged over each pixel pair, so flux is symmetric and the mean
+/// brightness is preserved. Image borders are insulating.
+///
+/// # Arguments
+/// * `iterations` - Number of diffusion steps; more means stronger smoothing
+/// * `kappa` - Edge threshold in grey levels; gradients well above it are preserved (10-30 typical)
+/// * `lambda` - Step size. The explicit 4-neighbour scheme is only stable for
+///   `lambda <= 0.25`; larger values overshoot and oscillate, so they are clamped to 0.25
+#[wasm_bindgen]
+pub fn anisotropic_diffusion(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    iterations: usize,
+    kappa: f32,
+    lambda: f32,
+) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+    if kappa <= 0.0 {
+        panic!("kappa must be positive");
+    }
+
+    let lambda = lambda.clamp(0.0, 0.25);
+    let rounding = RoundMode::current();
+    let mut image: Vec<f32> = input.iter().map(|&v| v as f32).collect();
+    let mut quantized = input.to_vec();
+    let mut next = vec![0.0f32; pixel_count];
+
+    for _ in 0..iterations {
+        // Sobel responds with 8x the per-pixel slope
+        let conduction: Vec<f32> = calculate_gradient_magnitude(&quantized, width, height)
+            .iter()
+            .map(|&m| {
+                let g = m / 8.0 / kappa;
+                1.0 / (1.0 + g * g)
+            })
+            .collect();
+
+        for y in 0..height {
+            for x in 0..width {
+                let i = y * width + x;
+                let mut flux = 0.0f32;
+                let mut neighbour = |n: usize| {
+                    flux += 0.5 * (conduction[i] + conduction[n]) * (image[n] - image[i]);
+                };
+                if x > 0 {
+                    neighbour(i - 1);
+                }
+                if x + 1 < width {
+                    neighbour(i + 1);
+                }
+                if y > 0 {
+                    neighbour(i - width);
+                }
+                if y + 1 < height {
+                    neighbour(i + width);
+                }
+                next[i] = image[i] + lambda * flux;
+            }
+        }
+
+        std::mem::swap(&mut image, &mut next);
+        for (q, &v) in quantized.iter_mut().zip(&image) {
+            *q = rounding.to_u8(v);
+        }
+    }
+
+    quantized
+}
@@ -11,9 +11,9 @@ unsafe fn dilate_fast(
     height: usize,
     kernel_size: usize,
     dilated: &mut [u8],
+    temp: &mut [u8],
 ) {
     let half_kernel = kernel_size / 2;
-    let mut temp = vec![0u8; width * height];
 
     // Horizontal pass (scalar for simplicity and because it's cache-friendly)
     for y in 0..height {
@@ -102,6 +102,11 @@ unsafe fn dilate_fast(
     }
 }
 
+/// Dilate with a flat `kernel_size` x `kernel_size` square, computed as a
+/// horizontal then a vertical max pass. Bit-identical to a direct 2D max over
+/// the square with replicated borders (OpenCV's `dilate` with
+/// `BORDER_REPLICATE`). Even `kernel_size`s place the window off-center,
+/// reaching one pixel further up and left; see `erode`.
 #[wasm_bindgen]
 pub fn dilate(
     edges: &[u8],
@@ -110,16 +115,30 @@ pub fn dilate(
     kernel_size: usize,
 ) -> Vec<u8> {
     let mut dilated = vec![0u8; width * height];
+    let mut temp = vec![0u8; width * height];
+    dilate_into(edges, width, height, kernel_size, &mut dilated, &mut temp);
+    dilated
+}
 
+/// `dilate` writing into caller-provided buffers, so repeated morphology
+/// (e.g. multi-iteration closing) can reuse allocations.
+/// `dilated` and `temp` must both hold `width * height` bytes.
+pub(crate) fn dilate_into(
+    edges: &[u8],
+    width: usize,
+    height: usize,
+    kernel_size: usize,
+    dilated: &mut [u8],
+    temp: &mut [u8],
+) {
     #[cfg(target_arch = "wasm32")]
     unsafe {
-        dilate_fast(edges, width, height, kernel_size, &mut dilated);
+        dilate_fast(edges, width, height, kernel_size, dilated, temp);
     }
 
     #[cfg(not(target_arch = "wasm32"))]
     {
         let half_kernel = kernel_size / 2;
-        let mut temp = vec![0u8; width * height];
         // Horizontal pass
         for y in 0..height {
             for x in 0..width {
@@ -152,6 +171,4 @@ pub fn dilate(
             }
         }
     }
-
-    dilated
 }
@@ -0,0 +1,126 @@
+use wasm_bindgen::prelude::*;
+
+/// Floyd-Steinberg error diffusion to a 1-bit (0/255) image.
+/// Each pixel is thresholded at 128 and its quantization error is pushed to
+/// the unvisited neighbours with the weights 7/16 (ahead), 3/16, 5/16, 1/16
+/// (next row). Rows are scanned serpentine - left to right, then right to
+/// left - with the kernel mirrored on the reverse rows, so the error doesn't
+/// drift in one direction and draw diagonal "worm" artifacts.
+#[wasm_bindgen]
+pub fn dither_floyd_steinberg(input: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    // Error carried into the current and the next row, padded by one pixel on
+    // each side so the kernel never needs bounds checks
+    let mut current = vec![0.0f32; width + 2];
+    let mut next = vec![0.0f32; width + 2];
+    let mut output = vec![0u8; pixel_count];
+
+    for y in 0..height {
+        let reverse = y % 2 == 1;
+        for i in 0..width {
+            let x = if reverse { width - 1 - i } else { i };
+            let value = input[y * width + x] as f32 + current[x + 1];
+            let quantized = if value >= 128.0 { 255u8 } else { 0u8 };
+            output[y * width + x] = quantized;
+
+            let error = value - quantized as f32;
+            // Padded indices of the pixel ahead and behind in scan direction
+            let (ahead, behind) = if reverse { (x, x + 2) } else { (x + 2, x) };
+            current[ahead] += error * 7.0 / 16.0;
+            next[behind] += error * 3.0 / 16.0;
+            next[x + 1] += error * 5.0 / 16.0;
+            next[ahead] += error * 1.0 / 16.0;
+        }
+        std::mem::swap(&mut current, &mut next);
+        next.fill(0.0);
+    }
+
+    output
+}
+
+/// Recursively built `n` x `n` Bayer index matrix (`n` a power of two).
+fn bayer_matrix(n: usize) -> Vec<u32> {
+    let mut matrix = vec![0u32];
+    let mut size = 1;
+    while size < n {
+        let mut grown = vec![0u32; 4 * size * size];
+        for y in 0..size {
+            for x in 0..size {
+                let v = 4 * matrix[y * size + x];
+                grown[y * 2 * size + x] = v;
+                grown[y * 2 * size + x + size] = v + 2;
+                grown[(y + size) * 2 * size + x] = v + 3;
+                grown[(y + size) * 2 * size + x + size] = v + 1;
+            }
+        }
+        matrix = grown;
+        size *= 2;
+    }
+    matrix
+}
+
+/// Ordered (Bayer) dither to a 1-bit (0/255) image.
+/// Each pixel is compared against a threshold from a tiled `matrix_size` x
+/// `matrix_size` Bayer matrix (2, 4 or 8). Unlike error diffusion every pixel
+/// is independent, giving a regular cross-hatch pattern that compresses well
+/// and doesn't shimmer when the input changes slightly.
+#[wasm_bindgen]
+pub fn dither_ordered(input: &[u8], width: usize, height: usize, matrix_size: usize) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+    if !matches!(matrix_size, 2 | 4 | 8) {
+        panic!("Bayer matrix size must be 2, 4 or 8");
+    }
+
+    // Thresholds at the centers of the n^2 equal intensity steps
+    let cells = (matrix_size * matrix_size) as f32;
+    let thresholds: Vec<f32> = bayer_matrix(matrix_size)
+        .iter()
+        .map(|&i| (i as f32 + 0.5) / cells * 255.0)
+        .collect();
+
+    let mut output = vec![0u8; pixel_count];
+    for y in 0..height {
+        let row = &thresholds[(y % matrix_size) * matrix_size..(y % matrix_size + 1) * matrix_size];
+        for x in 0..width {
+            output[y * width + x] = if input[y * width + x] as f32 > row[x % matrix_size] { 255 } else { 0 };
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mean(image: &[u8]) -> f64 {
+        image.iter().map(|&v| v as f64).sum::<f64>() / image.len() as f64
+    }
+
+    #[test]
+    fn test_dither_preserves_grey_level() {
+        let (width, height) = (64, 64);
+        for grey in [40u8, 100, 200] {
+            let input = vec![grey; width * height];
+            let output = dither_floyd_steinberg(&input, width, height);
+            assert!(output.iter().all(|&v| v == 0 || v == 255));
+            assert!((mean(&output) - grey as f64).abs() <= 3.0, "grey {grey}: mean {}", mean(&output));
+
+            // Ordered dither reproduces n^2 + 1 levels, so its mean is within
+            // half a level step
+            for n in [2, 4, 8] {
+                let output = dither_ordered(&input, width, height, n);
+                assert!(output.iter().all(|&v| v == 0 || v == 255));
+                let step = 255.0 / (n * n) as f64;
+                assert!((mean(&output) - grey as f64).abs() <= step / 2.0, "grey {grey}, {n}x{n}: mean {}", mean(&output));
+            }
+        }
+    }
+}
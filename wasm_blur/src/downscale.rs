@@ -0,0 +1,337 @@
+use wasm_bindgen::prelude::*;
+use crate::unsharp_mask::bilinear_sample;
+use crate::rounding::RoundMode;
+
+/// Downscale by an integer factor, averaging each `factor` x `factor` block.
+/// Output size is `(width / factor) x (height / factor)`; remainder rows and
+/// columns that don't fill a whole block are dropped, so every output pixel
+/// is the rounded mean of exactly `factor * factor` source pixels.
+#[wasm_bindgen]
+pub fn downscale_by(input: &[u8], width: usize, height: usize, factor: usize) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if factor == 0 {
+        panic!("Downscale factor must be greater than 0");
+    }
+    if factor == 1 {
+        return input.to_vec();
+    }
+
+    let out_width = width / factor;
+    let out_height = height / factor;
+    let block = (factor * factor) as u32;
+    let mut output = vec![0u8; out_width * out_height];
+    // Column sums for the current band of `factor` rows
+    let mut sums = vec![0u32; out_width];
+
+    for oy in 0..out_height {
+        sums.iter_mut().for_each(|s| *s = 0);
+        for y in oy * factor..(oy + 1) * factor {
+            let row = &input[y * width..y * width + out_width * factor];
+            for (ox, chunk) in row.chunks_exact(factor).enumerate() {
+                sums[ox] += chunk.iter().map(|&v| v as u32).sum::<u32>();
+            }
+        }
+        let out_row = &mut output[oy * out_width..(oy + 1) * out_width];
+        for (dst, &sum) in out_row.iter_mut().zip(sums.iter()) {
+            *dst = ((sum + block / 2) / block) as u8;
+        }
+    }
+
+    output
+}
+
+/// Largest size within `max_width` x `max_height` with the aspect ratio of
+/// `width` x `height`, as `[width, height]`, for picking the target of the
+/// fused downscale functions or `resize_separable`. The bounding side is
+/// matched exactly and the other is rounded to the nearest pixel (at least
+/// 1) in integer arithmetic, so exact ratios stay exact: a 4000x3000 image
+/// fitted to 2000x2000 gives 2000x1500, the size `downscale_by` with factor
+/// 2 produces. Images already within the bounds keep their size; this never
+/// enlarges.
+#[wasm_bindgen]
+pub fn fit_dimensions(width: usize, height: usize, max_width: usize, max_height: usize) -> Vec<usize> {
+    if width == 0 || height == 0 {
+        panic!("Input dimensions must be greater than 0");
+    }
+    if max_width == 0 || max_height == 0 {
+        panic!("Target dimensions must be greater than 0");
+    }
+    if width <= max_width && height <= max_height {
+        return vec![width, height];
+    }
+
+    // Compare max_width / width against max_height / height without division
+    let (w, h) = (width as u64, height as u64);
+    let (mw, mh) = (max_width as u64, max_height as u64);
+    if mw * h <= mh * w {
+        let fitted = ((2 * h * mw + w) / (2 * w)).clamp(1, mh);
+        vec![max_width, fitted as usize]
+    } else {
+        let fitted = ((2 * w * mh + h) / (2 * h)).clamp(1, mw);
+        vec![fitted as usize, max_height]
+    }
+}
+
+/// How the fused downscale functions (`clahe_and_downscale`,
+/// `unsharp_mask_and_downscale`) sample the source image.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SampleMode {
+    /// Nearest source pixel; fastest, but aliases at large factors
+    Nearest,
+    /// Blend of the four nearest source pixels
+    Bilinear,
+    /// Mean of all source pixels covered by the output pixel; best quality
+    Area,
+}
+
+/// Source position of the center of output pixel `o` when `len` source
+/// pixels are mapped onto `target_len`.
+#[inline]
+fn source_center(o: usize, len: usize, target_len: usize) -> f64 {
+    (o as f64 + 0.5) * (len as f64 / target_len as f64) - 0.5
+}
+
+/// Nearest source index for output pixel `o`.
+pub(crate) fn nearest_index(o: usize, len: usize, target_len: usize) -> usize {
+    source_center(o, len, target_len).round().clamp(0.0, (len - 1) as f64) as usize
+}
+
+/// The two source indices around output pixel `o` and the weight of the
+/// second one, clamped at the edges.
+pub(crate) fn bilinear_taps(o: usize, len: usize, target_len: usize) -> (usize, usize, f32) {
+    let src = source_center(o, len, target_len);
+    let floor = src.floor();
+    let frac = (src - floor) as f32;
+    let i0 = floor.clamp(0.0, (len - 1) as f64) as usize;
+    let i1 = (floor + 1.0).clamp(0.0, (len - 1) as f64) as usize;
+    (i0, i1, frac)
+}
+
+/// Source range `[start, end)` averaged into output pixel `o` when `len`
+/// source pixels are shrunk to `target_len`. Ranges of neighbouring outputs
+/// tile the source without overlap and hold at least one pixel each.
+pub(crate) fn area_bounds(o: usize, len: usize, target_len: usize) -> (usize, usize) {
+    let start = o * len / target_len;
+    let end = ((o + 1) * len / target_len).max(start + 1).min(len);
+    (start, end)
+}
+
+/// Bilinear resize to an arbitrary size using the same pixel-center mapping
+/// as the fused downscale functions.
+pub(crate) fn resize_bilinear(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    target_width: usize,
+    target_height: usize,
+) -> Vec<u8> {
+    let rounding = RoundMode::current();
+    let mut output = vec![0u8; target_width * target_height];
+    let sx = width as f64 / target_width as f64;
+    let sy = height as f64 / target_height as f64;
+
+    for oy in 0..target_height {
+        let src_y = (oy as f64 + 0.5) * sy - 0.5;
+        let src_y_floor = src_y.floor() as isize;
+        let fy = (src_y - src_y_floor as f64) as f32;
+
+        for ox in 0..target_width {
+            let src_x = (ox as f64 + 0.5) * sx - 0.5;
+            let src_x_floor = src_x.floor() as isize;
+            let fx = (src_x - src_x_floor as f64) as f32;
+
+            let value = bilinear_sample(input, width, height, src_x_floor, src_y_floor, fx, fy);
+            output[oy * target_width + ox] = rounding.to_u8(value);
+        }
+    }
+
+    output
+}
+
+/// Contributions of the source samples to each of `target_len` outputs, as
+/// `(first source index, weights)` with weights summing to 1.
+/// Shrinking uses exact area coverage: output `o` averages the source
+/// interval `[o * scale, (o + 1) * scale)`, counting partially covered pixels
+/// by their overlap. Enlarging uses linear interpolation between the two
+/// nearest pixel centers (edges clamped), as area coverage would degenerate to
+/// nearest-neighbour.
+fn resample_taps(len: usize, target_len: usize) -> Vec<(usize, Vec<f32>)> {
+    let scale = len as f64 / target_len as f64;
+    (0..target_len)
+        .map(|o| {
+            if scale >= 1.0 {
+                let start = o as f64 * scale;
+                let end = start + scale;
+                let first = start.floor() as usize;
+                let last = (end.ceil() as usize).min(len);
+                let weights = (first..last)
+                    .map(|i| {
+                        let overlap = end.min(i as f64 + 1.0) - start.max(i as f64);
+                        (overlap.max(0.0) / scale) as f32
+                    })
+                    .collect();
+                (first, weights)
+            } else {
+                let (i0, i1, frac) = bilinear_taps(o, len, target_len);
+                if i0 == i1 {
+                    (i0, vec![1.0])
+                } else {
+                    (i0, vec![1.0 - frac, frac])
+                }
+            }
+        })
+        .collect()
+}
+
+/// High-quality resize to exactly `target_width` x `target_height`.
+/// Separable: rows are resampled horizontally into a float buffer, then
+/// columns vertically, rounding once at the end. Downscaling averages the
+/// exact area each output pixel covers (fractional pixels included), so any
+/// ratio is alias-free and integer factors give the same block means as
+/// `downscale_by`; upscaling interpolates linearly. Use this as the
+/// reference for the fused `SampleMode::Area` paths, which snap the
+/// footprint to whole source pixels.
+#[wasm_bindgen]
+pub fn resize_separable(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    target_width: usize,
+    target_height: usize,
+) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if target_width == 0 || target_height == 0 {
+        panic!("Target dimensions must be greater than 0");
+    }
+    if width == 0 || height == 0 {
+        panic!("Input dimensions must be greater than 0");
+    }
+
+    let x_taps = resample_taps(width, target_width);
+    let y_taps = resample_taps(height, target_height);
+
+    let mut horizontal = vec![0.0f32; target_width * height];
+    for y in 0..height {
+        let row = &input[y * width..(y + 1) * width];
+        let out_row = &mut horizontal[y * target_width..(y + 1) * target_width];
+        for (out, (first, weights)) in out_row.iter_mut().zip(&x_taps) {
+            *out = weights.iter().zip(&row[*first..]).map(|(&w, &v)| w * v as f32).sum();
+        }
+    }
+
+    let rounding = RoundMode::current();
+    let mut output = vec![0u8; target_width * target_height];
+    let mut acc = vec![0.0f32; target_width];
+    for (oy, (first, weights)) in y_taps.iter().enumerate() {
+        acc.fill(0.0);
+        for (k, &w) in weights.iter().enumerate() {
+            let row = &horizontal[(first + k) * target_width..(first + k + 1) * target_width];
+            for (a, &v) in acc.iter_mut().zip(row) {
+                *a += w * v;
+            }
+        }
+        for (out, &v) in output[oy * target_width..(oy + 1) * target_width].iter_mut().zip(&acc) {
+            *out = rounding.to_u8(v);
+        }
+    }
+
+    output
+}
+
+/// Mipmap-style downscale for large reduction factors: the image is halved
+/// with 2x2 box averaging while it stays at least twice the target size, then
+/// a final bilinear step lands exactly on `target_width` x `target_height`.
+/// Every source pixel contributes to the result, so this avoids the aliasing
+/// of a single bilinear jump (e.g. 12MP straight down to 400px).
+#[wasm_bindgen]
+pub fn downscale_pyramid(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    target_width: usize,
+    target_height: usize,
+) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if target_width == 0 || target_height == 0 {
+        panic!("Target dimensions must be greater than 0");
+    }
+
+    let mut current = input.to_vec();
+    let mut cur_width = width;
+    let mut cur_height = height;
+
+    while cur_width / 2 >= target_width && cur_height / 2 >= target_height {
+        current = downscale_by(&current, cur_width, cur_height, 2);
+        cur_width /= 2;
+        cur_height /= 2;
+    }
+
+    if cur_width == target_width && cur_height == target_height {
+        return current;
+    }
+
+    resize_bilinear(&current, cur_width, cur_height, target_width, target_height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Direct 2D area average over the exact source footprint of each output pixel.
+    fn reference_area(input: &[u8], width: usize, height: usize, tw: usize, th: usize) -> Vec<u8> {
+        let (sx, sy) = (width as f64 / tw as f64, height as f64 / th as f64);
+        let mut output = vec![0u8; tw * th];
+        for oy in 0..th {
+            for ox in 0..tw {
+                let (x0, x1) = (ox as f64 * sx, (ox + 1) as f64 * sx);
+                let (y0, y1) = (oy as f64 * sy, (oy + 1) as f64 * sy);
+                let mut sum = 0.0;
+                for y in 0..height {
+                    let wy = (y1.min(y as f64 + 1.0) - y0.max(y as f64)).max(0.0);
+                    for x in 0..width {
+                        let wx = (x1.min(x as f64 + 1.0) - x0.max(x as f64)).max(0.0);
+                        sum += wx * wy * input[y * width + x] as f64;
+                    }
+                }
+                output[oy * tw + ox] = (sum / (sx * sy)).round() as u8;
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn test_resize_separable_matches_area_reference() {
+        let (width, height) = (42, 30);
+        let input: Vec<u8> = (0..width * height).map(|i| ((i * 37 + i / width * 11) % 256) as u8).collect();
+
+        for (tw, th) in [(21, 15), (14, 10), (28, 20), (18, 13), (5, 7)] {
+            let actual = resize_separable(&input, width, height, tw, th);
+            let expected = reference_area(&input, width, height, tw, th);
+            for (a, e) in actual.iter().zip(&expected) {
+                // Float summation order may flip an exact .5 tie
+                assert!((*a as i32 - *e as i32).abs() <= 1, "{}x{}: {} vs {}", tw, th, a, e);
+            }
+        }
+
+        assert_eq!(resize_separable(&input, width, height, 14, 10), downscale_by(&input, width, height, 3));
+        assert_eq!(resize_separable(&input, width, height, width, height), input);
+    }
+
+    #[test]
+    fn test_fit_dimensions() {
+        assert_eq!(fit_dimensions(4000, 3000, 2000, 2000), vec![2000, 1500]);
+        assert_eq!(fit_dimensions(3000, 4000, 800, 800), vec![600, 800]);
+        // 1001 * 500 / 1000 = 500.5 rounds up
+        assert_eq!(fit_dimensions(1000, 1001, 1000, 500), vec![500, 500]);
+        assert_eq!(fit_dimensions(1000, 1001, 500, 1000), vec![500, 501]);
+        assert_eq!(fit_dimensions(10000, 3, 100, 100), vec![100, 1]);
+        assert_eq!(fit_dimensions(640, 480, 1920, 1080), vec![640, 480]);
+    }
+}
@@ -0,0 +1,143 @@
+use wasm_bindgen::prelude::*;
+
+// Bresenham circle of radius 3 around the candidate pixel, clockwise from the top.
+const CIRCLE: [(isize, isize); 16] = [
+    (0, -3), (1, -3), (2, -2), (3, -1),
+    (3, 0), (3, 1), (2, 2), (1, 3),
+    (0, 3), (-1, 3), (-2, 2), (-3, 1),
+    (-3, 0), (-3, -1), (-2, -2), (-1, -3),
+];
+
+/// Length of the longest run of `true` on the circle, wrapping around.
+#[inline]
+fn longest_arc(flags: &[bool; 16]) -> usize {
+    let mut best = 0;
+    let mut run = 0;
+    // Walk the circle twice so runs crossing index 0 are counted once in full
+    for i in 0..32 {
+        if flags[i % 16] {
+            run += 1;
+            if run > best {
+                best = run;
+            }
+        } else {
+            run = 0;
+        }
+    }
+    best.min(16)
+}
+
+/// FAST (Features from Accelerated Segment Test) corner detector.
+/// A pixel is a corner when at least `n` contiguous pixels on the radius-3
+/// circle around it are all brighter than `p + threshold` or all darker than
+/// `p - threshold`. `n` is typically 9 (FAST-9) or 12 (FAST-12).
+///
+/// The score of a corner is the sum of absolute differences beyond the
+/// threshold over the dominant (brighter or darker) circle pixels. With
+/// `nonmax_suppression` only corners that are maximal in their 3x3
+/// neighbourhood are kept.
+///
+/// # Returns
+/// Flat `[x, y, score, ...]` triplets sorted by descending score, so the
+/// strongest N corners are simply the first 3*N values.
+#[wasm_bindgen]
+pub fn fast_corners(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    threshold: u8,
+    n: usize,
+    nonmax_suppression: bool,
+) -> Vec<f32> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+    if n == 0 || n > 16 {
+        panic!("Arc length n must be between 1 and 16");
+    }
+
+    if width < 7 || height < 7 {
+        return Vec::new();
+    }
+
+    let mut scores = vec![0u32; pixel_count];
+
+    let offsets: Vec<isize> = CIRCLE
+        .iter()
+        .map(|&(dx, dy)| dy * width as isize + dx)
+        .collect();
+    let t = threshold as i32;
+
+    for y in 3..height - 3 {
+        for x in 3..width - 3 {
+            let idx = y * width + x;
+            let p = input[idx] as i32;
+
+            let mut brighter = [false; 16];
+            let mut darker = [false; 16];
+            let mut bright_sum = 0u32;
+            let mut dark_sum = 0u32;
+            for (i, &off) in offsets.iter().enumerate() {
+                let v = input[(idx as isize + off) as usize] as i32;
+                if v > p + t {
+                    brighter[i] = true;
+                    bright_sum += (v - p - t) as u32;
+                } else if v < p - t {
+                    darker[i] = true;
+                    dark_sum += (p - t - v) as u32;
+                }
+            }
+
+            if longest_arc(&brighter) >= n || longest_arc(&darker) >= n {
+                // Keep scores strictly positive so a zero means "not a corner"
+                scores[idx] = bright_sum.max(dark_sum).max(1);
+            }
+        }
+    }
+
+    let mut corners: Vec<(usize, usize, u32)> = Vec::new();
+    for y in 3..height - 3 {
+        for x in 3..width - 3 {
+            let idx = y * width + x;
+            let score = scores[idx];
+            if score == 0 {
+                continue;
+            }
+
+            if nonmax_suppression {
+                let mut is_max = true;
+                'neighbours: for dy in -1isize..=1 {
+                    for dx in -1isize..=1 {
+                        if dx == 0 && dy == 0 {
+                            continue;
+                        }
+                        let nidx = (idx as isize + dy * width as isize + dx) as usize;
+                        let other = scores[nidx];
+                        // Break ties towards the earlier pixel in scan order
+                        if other > score || (other == score && nidx < idx) {
+                            is_max = false;
+                            break 'neighbours;
+                        }
+                    }
+                }
+                if !is_max {
+                    continue;
+                }
+            }
+
+            corners.push((x, y, score));
+        }
+    }
+
+    corners.sort_by_key(|c| std::cmp::Reverse(c.2));
+
+    let mut result = Vec::with_capacity(corners.len() * 3);
+    for (x, y, score) in corners {
+        result.push(x as f32);
+        result.push(y as f32);
+        result.push(score as f32);
+    }
+
+    result
+}
@@ -1,4 +1,6 @@
 use wasm_bindgen::prelude::*;
+use crate::border::{pad_image, BorderMode};
+use crate::roi::crop;
 use std::arch::wasm32::*;
 
 // Constants for optimization
@@ -21,31 +23,50 @@ fn from_fixed_point(val: FixedPoint) -> f32 {
     val as f32 / FIXED_POINT_SCALE
 }
 
-// Optimized 1D Gaussian kernel creation with fixed-point arithmetic
-#[inline]
-pub fn create_gaussian_kernel_fixed(size: usize, sigma: f32) -> Vec<FixedPoint> {
-    let mut kernel = Vec::with_capacity(size);
-    let mut float_kernel = Vec::with_capacity(size);
-    
+// Normalized 1D Gaussian kernel in floating point
+pub(crate) fn create_gaussian_kernel(size: usize, sigma: f32) -> Vec<f32> {
     let half_size = (size / 2) as i32;
     let neg_inv_2sigma_sq = -1.0 / (2.0 * sigma * sigma);
-    let mut sum = 0.0;
 
-    // Calculate kernel values in floating point first
-    for i in 0..size {
-        let x = i as i32 - half_size;
-        let val = ((x * x) as f32 * neg_inv_2sigma_sq).exp();
-        float_kernel.push(val);
-        sum += val;
-    }
+    let mut kernel: Vec<f32> = (0..size)
+        .map(|i| {
+            let x = i as i32 - half_size;
+            ((x * x) as f32 * neg_inv_2sigma_sq).exp()
+        })
+        .collect();
+    let inv_sum = 1.0 / kernel.iter().sum::<f32>();
+    kernel.iter_mut().for_each(|v| *v *= inv_sum);
+
+    kernel
+}
 
-    // Normalize and convert to fixed point
-    let inv_sum = 1.0 / sum;
-    for &val in float_kernel.iter() {
-        kernel.push(to_fixed_point(val * inv_sum));
+/// Kernel length for a Gaussian of `sigma`: `round(6 * sigma + 1)`, made odd,
+/// i.e. about three sigma on each side. This is OpenCV's rule for 8-bit
+/// images, so `blur(img, w, h, gaussian_kernel_size(s), s)` uses the same
+/// kernel as `cv.GaussianBlur(img, new cv.Size(0, 0), s)`. (The reverse, the sigma
+/// `blur` picks for a given kernel size when `sigma <= 0`, is OpenCV's
+/// `0.3 * ((size - 1) / 2 - 1) + 0.8`.)
+#[wasm_bindgen]
+pub fn gaussian_kernel_size(sigma: f32) -> usize {
+    if !(sigma > 0.0 && sigma.is_finite()) {
+        panic!("Sigma must be greater than 0");
     }
+    (6.0 * sigma + 1.0).round() as usize | 1
+}
 
-    kernel
+/// Normalized 1D Gaussian of `sigma` with `gaussian_kernel_size(sigma)`
+/// taps, the weights `blur` uses along each axis. Pass it to
+/// `convolve_separable` (twice, or with a derivative kernel on one axis) to
+/// build custom Gaussian-derived filters.
+#[wasm_bindgen]
+pub fn gaussian_kernel_1d(sigma: f32) -> Vec<f32> {
+    create_gaussian_kernel(gaussian_kernel_size(sigma), sigma)
+}
+
+// Optimized 1D Gaussian kernel creation with fixed-point arithmetic
+#[inline]
+pub fn create_gaussian_kernel_fixed(size: usize, sigma: f32) -> Vec<FixedPoint> {
+    create_gaussian_kernel(size, sigma).into_iter().map(to_fixed_point).collect()
 }
 
 // Optimized horizontal pass with fixed-point arithmetic
@@ -425,3 +446,251 @@ pub fn blur(
 
     result
 }
+
+/// `blur` for an image whose rows start `stride` bytes apart. The result is
+/// tightly packed (`width * height` bytes).
+#[wasm_bindgen]
+pub fn blur_strided(
+    grayscale: &[u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+    kernel_size: usize,
+    sigma: f32,
+) -> Vec<u8> {
+    let packed = crate::memory::packed_rows(grayscale, width, height, stride);
+    blur(&packed, width, height, kernel_size, sigma)
+}
+
+/// `blur` for a floating-point image (e.g. normalized to [0, 1]), keeping
+/// full precision between pipeline stages instead of quantizing to u8. Same
+/// kernel and replicated edges as `blur`; values are not clamped.
+#[wasm_bindgen]
+pub fn blur_f32(
+    input: &[f32],
+    width: usize,
+    height: usize,
+    kernel_size: usize,
+    mut sigma: f32,
+) -> Vec<f32> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if kernel_size == 0 || kernel_size.is_multiple_of(2) {
+        panic!("Kernel size must be odd and greater than 0");
+    }
+    if sigma <= 0.0 {
+        sigma = 0.3 * (((kernel_size - 1) as f32) * 0.5 - 1.0) + 0.8;
+    }
+
+    let kernel = create_gaussian_kernel(kernel_size, sigma);
+    let half = (kernel_size / 2) as isize;
+    let mut temp = vec![0.0f32; width * height];
+    let mut result = vec![0.0f32; width * height];
+
+    for y in 0..height {
+        let row = &input[y * width..(y + 1) * width];
+        for x in 0..width {
+            temp[y * width + x] = kernel
+                .iter()
+                .enumerate()
+                .map(|(k, &w)| w * row[(x as isize + k as isize - half).clamp(0, width as isize - 1) as usize])
+                .sum();
+        }
+    }
+    for y in 0..height {
+        for x in 0..width {
+            result[y * width + x] = kernel
+                .iter()
+                .enumerate()
+                .map(|(k, &w)| {
+                    let sy = (y as isize + k as isize - half).clamp(0, height as isize - 1) as usize;
+                    w * temp[sy * width + x]
+                })
+                .sum();
+        }
+    }
+
+    result
+}
+
+/// Difference of Gaussians band-pass: `blur(sigma1) - blur(sigma2)`, offset
+/// to mid-gray so the bipolar response fits in u8 (128 is zero, as for
+/// `emboss`). With `sigma1 < sigma2` it keeps structures between the two
+/// scales and approximates a negated Laplacian of Gaussian at about
+/// `sqrt(sigma1 * sigma2)`: bright blobs of that size come out above 128,
+/// dark ones below. Both blurs use `gaussian_kernel_size` taps and are
+/// subtracted in floating point, so weak responses are not lost to
+/// rounding; differences beyond +-127 are clamped.
+#[wasm_bindgen]
+pub fn difference_of_gaussians(input: &[u8], width: usize, height: usize, sigma1: f32, sigma2: f32) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let data: Vec<f32> = input.iter().map(|&v| v as f32).collect();
+    let fine = blur_f32(&data, width, height, gaussian_kernel_size(sigma1), sigma1);
+    let coarse = blur_f32(&data, width, height, gaussian_kernel_size(sigma2), sigma2);
+
+    let rounding = crate::rounding::RoundMode::current();
+    fine.iter().zip(&coarse).map(|(&f, &c)| rounding.to_u8(128.0 + f - c)).collect()
+}
+
+/// Box widths for `passes` successive box blurs approximating a Gaussian of
+/// `sigma` (Kovesi, "Fast almost-Gaussian filtering"): the odd widths just
+/// below and above the ideal one, mixed so the total variance matches.
+fn box_widths(sigma: f32, passes: usize) -> Vec<usize> {
+    let n = passes as f32;
+    let ideal = (12.0 * sigma * sigma / n + 1.0).sqrt();
+    let mut lower = ideal.floor() as usize;
+    if lower.is_multiple_of(2) {
+        lower -= 1;
+    }
+    let wl = lower as f32;
+    let lower_count = ((12.0 * sigma * sigma - n * wl * wl - 4.0 * n * wl - 3.0 * n) / (-4.0 * wl - 4.0))
+        .round()
+        .clamp(0.0, n) as usize;
+    (0..passes).map(|i| if i < lower_count { lower } else { lower + 2 }).collect()
+}
+
+/// Sliding-window mean of radius `radius` along one line, with replicated
+/// edges; reads `src` at `stride` steps and writes `dst` the same way.
+fn box_pass(src: &[f32], dst: &mut [f32], len: usize, stride: usize, radius: usize) {
+    let at = |i: isize| src[i.clamp(0, len as isize - 1) as usize * stride];
+    let r = radius as isize;
+    let scale = 1.0 / (2 * radius + 1) as f32;
+    let mut sum: f32 = (-r..=r).map(at).sum();
+    for i in 0..len as isize {
+        dst[i as usize * stride] = sum * scale;
+        sum += at(i + r + 1) - at(i - r);
+    }
+}
+
+/// Approximate Gaussian blur by `passes` repeated box blurs (3 is the usual
+/// balance of quality and speed), with box widths chosen to match `sigma`.
+/// Every box pass is a sliding window, so the cost does not depend on
+/// `sigma`; intermediate results stay in floating point and are rounded
+/// once. Borders are replicated.
+///
+/// # Arguments
+/// * `sigma` - Standard deviation of the Gaussian; below 0.5 the input is
+///   returned unchanged
+/// * `passes` - Number of box blurs; more passes get closer to a Gaussian
+#[wasm_bindgen]
+pub fn gaussian_box_approx(input: &[u8], width: usize, height: usize, sigma: f32, passes: usize) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+    if passes == 0 || sigma < 0.5 || pixel_count == 0 {
+        return input.to_vec();
+    }
+
+    let mut data: Vec<f32> = input.iter().map(|&v| v as f32).collect();
+    let mut temp = vec![0.0f32; pixel_count];
+    for box_width in box_widths(sigma, passes) {
+        let radius = box_width / 2;
+        for y in 0..height {
+            let row = y * width..(y + 1) * width;
+            box_pass(&data[row.clone()], &mut temp[row], width, 1, radius);
+        }
+        for x in 0..width {
+            box_pass(&temp[x..], &mut data[x..], height, width, radius);
+        }
+    }
+
+    let rounding = crate::rounding::RoundMode::current();
+    data.into_iter().map(|v| rounding.to_u8(v)).collect()
+}
+
+/// `blur` with a selectable border mode instead of replicated edges, e.g.
+/// `BorderMode.Wrap` so a tileable texture stays seamless.
+#[wasm_bindgen]
+pub fn blur_with_border(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    kernel_size: usize,
+    sigma: f32,
+    border: BorderMode,
+) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+
+    let pad = kernel_size / 2;
+    let padded = pad_image(input, width, height, pad, border);
+    let (padded_width, padded_height) = (width + 2 * pad, height + 2 * pad);
+    let blurred = blur(&padded, padded_width, padded_height, kernel_size, sigma);
+    crop(&blurred, padded_width, pad, pad, width, height)
+}
+
+/// Recursive (IIR) Gaussian blur of an `f32` image in place, after Young &
+/// van Vliet: a causal and an anti-causal third-order filter per axis. The
+/// cost is a handful of operations per pixel regardless of `sigma`, which
+/// makes it the choice for very large radii (illumination estimates and the
+/// like). Borders behave like replicated edges; `sigma` below 0.5 leaves the
+/// data unchanged.
+pub(crate) fn gaussian_iir(data: &mut [f32], width: usize, height: usize, sigma: f32) {
+    if data.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if width == 0 || height == 0 || sigma < 0.5 {
+        return;
+    }
+
+    let sigma = sigma as f64;
+    let q = if sigma >= 2.5 {
+        0.98711 * sigma - 0.96330
+    } else {
+        3.97156 - 4.14554 * (1.0 - 0.26891 * sigma).sqrt()
+    };
+    let b0 = 1.57825 + 2.44413 * q + 1.4281 * q * q + 0.422205 * q * q * q;
+    let b1 = (2.44413 * q + 2.85619 * q * q + 1.26661 * q * q * q) / b0;
+    let b2 = -(1.4281 * q * q + 1.26661 * q * q * q) / b0;
+    let b3 = 0.422205 * q * q * q / b0;
+    let coeffs = [1.0 - (b1 + b2 + b3), b1, b2, b3];
+    let mut line = vec![0.0f64; width.max(height)];
+    for y in 0..height {
+        let row = &mut data[y * width..(y + 1) * width];
+        for (l, &v) in line.iter_mut().zip(row.iter()) {
+            *l = v as f64;
+        }
+        recursive_pass(&mut line[..width], coeffs);
+        for (v, &l) in row.iter_mut().zip(line.iter()) {
+            *v = l as f32;
+        }
+    }
+    for x in 0..width {
+        for y in 0..height {
+            line[y] = data[y * width + x] as f64;
+        }
+        recursive_pass(&mut line[..height], coeffs);
+        for y in 0..height {
+            data[y * width + x] = line[y] as f32;
+        }
+    }
+}
+
+/// Forward then backward third-order recursion over one line; both start in
+/// the steady state of a constant signal equal to the edge value.
+fn recursive_pass(line: &mut [f64], [b, b1, b2, b3]: [f64; 4]) {
+    let first = line[0];
+    let (mut w1, mut w2, mut w3) = (first, first, first);
+    for v in line.iter_mut() {
+        let w = b * *v + b1 * w1 + b2 * w2 + b3 * w3;
+        *v = w;
+        (w3, w2, w1) = (w2, w1, w);
+    }
+
+    let last = line[line.len() - 1];
+    let (mut y1, mut y2, mut y3) = (last, last, last);
+    for v in line.iter_mut().rev() {
+        let y = b * *v + b1 * y1 + b2 * y2 + b3 * y3;
+        *v = y;
+        (y3, y2, y1) = (y2, y1, y);
+    }
+}
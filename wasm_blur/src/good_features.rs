@@ -0,0 +1,226 @@
+use wasm_bindgen::prelude::*;
+use crate::gradient_calculation::calculate_sobel_gradients;
+
+/// Structure tensor components (Ixx, Ixy, Iyy) summed over a
+/// `block_size` x `block_size` window around each pixel.
+/// `gradients` is the interleaved `[gx, gy, ...]` layout from the gradient functions.
+pub(crate) fn structure_tensor(
+    gradients: &[i16],
+    width: usize,
+    height: usize,
+    block_size: usize,
+) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
+    let size = width * height;
+    let half = (block_size / 2) as isize;
+
+    // Per-pixel products
+    let mut xx = vec![0.0f32; size];
+    let mut xy = vec![0.0f32; size];
+    let mut yy = vec![0.0f32; size];
+    for i in 0..size {
+        let gx = gradients[2 * i] as f32;
+        let gy = gradients[2 * i + 1] as f32;
+        xx[i] = gx * gx;
+        xy[i] = gx * gy;
+        yy[i] = gy * gy;
+    }
+
+    // Separable box sum: horizontal pass
+    let mut temp = [vec![0.0f32; size], vec![0.0f32; size], vec![0.0f32; size]];
+    for y in 0..height {
+        let row = y * width;
+        for x in 0..width {
+            let mut sums = [0.0f32; 3];
+            for k in -half..=half {
+                let nx = (x as isize + k).clamp(0, (width - 1) as isize) as usize;
+                sums[0] += xx[row + nx];
+                sums[1] += xy[row + nx];
+                sums[2] += yy[row + nx];
+            }
+            temp[0][row + x] = sums[0];
+            temp[1][row + x] = sums[1];
+            temp[2][row + x] = sums[2];
+        }
+    }
+
+    // Vertical pass, written back into the product buffers
+    for y in 0..height {
+        for x in 0..width {
+            let mut sums = [0.0f32; 3];
+            for k in -half..=half {
+                let ny = (y as isize + k).clamp(0, (height - 1) as isize) as usize;
+                sums[0] += temp[0][ny * width + x];
+                sums[1] += temp[1][ny * width + x];
+                sums[2] += temp[2][ny * width + x];
+            }
+            xx[y * width + x] = sums[0];
+            xy[y * width + x] = sums[1];
+            yy[y * width + x] = sums[2];
+        }
+    }
+
+    (xx, xy, yy)
+}
+
+/// Harris corner response `det(M) - k * trace(M)^2` of the 3x3 structure
+/// tensor `M` for every pixel, as a `Float32Array`. Positive values are
+/// corners, negative values edges; `k` is typically 0.04-0.06.
+///
+/// ```js
+/// const response = harris_response(gray, width, height, 0.04);
+/// const max = response.reduce((a, b) => Math.max(a, b), 0);
+/// ```
+#[wasm_bindgen]
+pub fn harris_response(input: &[u8], width: usize, height: usize, k: f32) -> Vec<f32> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    let gradients = calculate_sobel_gradients(input, width, height);
+    let (xx, xy, yy) = structure_tensor(&gradients, width, height, 3);
+
+    (0..width * height)
+        .map(|i| {
+            let det = xx[i] * yy[i] - xy[i] * xy[i];
+            let trace = xx[i] + yy[i];
+            det - k * trace * trace
+        })
+        .collect()
+}
+
+/// Minimum eigenvalue of the 3x3 structure tensor for every pixel (the
+/// Shi-Tomasi response used by `good_features`), as a `Float32Array`.
+#[wasm_bindgen]
+pub fn min_eigen_response(input: &[u8], width: usize, height: usize) -> Vec<f32> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    let gradients = calculate_sobel_gradients(input, width, height);
+    let (xx, xy, yy) = structure_tensor(&gradients, width, height, 3);
+    min_eigenvalues(&xx, &xy, &yy)
+}
+
+/// Minimum eigenvalue of `[[xx, xy], [xy, yy]]` per pixel.
+fn min_eigenvalues(xx: &[f32], xy: &[f32], yy: &[f32]) -> Vec<f32> {
+    xx.iter()
+        .zip(xy.iter())
+        .zip(yy.iter())
+        .map(|((&xx, &xy), &yy)| {
+            let a = xx * 0.5;
+            let c = yy * 0.5;
+            (a + c) - ((a - c) * (a - c) + xy * xy).sqrt()
+        })
+        .collect()
+}
+
+/// Shi-Tomasi corner detector, equivalent to OpenCV's `goodFeaturesToTrack`.
+/// The corner response is the minimum eigenvalue of the 3x3 structure tensor
+/// built from Sobel gradients. Corners weaker than `quality_level` times the
+/// best response are discarded, the rest are reduced to 3x3 local maxima and
+/// then taken strongest-first while keeping at least `min_distance` pixels
+/// between accepted corners.
+///
+/// # Arguments
+/// * `max_corners` - Maximum number of corners to return (0 means no limit)
+/// * `quality_level` - Fraction of the strongest response a corner must reach (e.g. 0.01)
+/// * `min_distance` - Minimum Euclidean distance between returned corners
+///
+/// # Returns
+/// Flat `[x, y, ...]` pairs ordered by descending corner strength
+#[wasm_bindgen]
+pub fn good_features(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    max_corners: usize,
+    quality_level: f32,
+    min_distance: f32,
+) -> Vec<f32> {
+    let size = width * height;
+    if input.len() != size {
+        panic!("Input array size doesn't match width * height");
+    }
+    if size == 0 {
+        return Vec::new();
+    }
+
+    let gradients = calculate_sobel_gradients(input, width, height);
+    let (xx, xy, yy) = structure_tensor(&gradients, width, height, 3);
+
+    let response = min_eigenvalues(&xx, &xy, &yy);
+    let max_response = response.iter().copied().fold(0.0f32, f32::max);
+    if max_response <= 0.0 {
+        return Vec::new();
+    }
+
+    // Quality threshold and 3x3 local-maximum test
+    let threshold = max_response * quality_level;
+    let mut candidates: Vec<(f32, usize, usize)> = Vec::new();
+    for y in 1..height.saturating_sub(1) {
+        for x in 1..width.saturating_sub(1) {
+            let idx = y * width + x;
+            let val = response[idx];
+            if val <= threshold {
+                continue;
+            }
+            let mut is_max = true;
+            for dy in -1isize..=1 {
+                for dx in -1isize..=1 {
+                    let nidx = (idx as isize + dy * width as isize + dx) as usize;
+                    if response[nidx] > val {
+                        is_max = false;
+                    }
+                }
+            }
+            if is_max {
+                candidates.push((val, x, y));
+            }
+        }
+    }
+
+    // Strongest first; stable sort keeps scan order among equal responses
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    // Greedy min-distance suppression using a coarse grid of accepted corners
+    let min_dist = min_distance.max(0.0);
+    let min_dist_sq = min_dist * min_dist;
+    let cell = (min_dist.ceil() as usize).max(1);
+    let grid_w = width.div_ceil(cell);
+    let grid_h = height.div_ceil(cell);
+    let mut grid: Vec<Vec<(usize, usize)>> = vec![Vec::new(); grid_w * grid_h];
+
+    let mut result = Vec::new();
+    let mut accepted = 0;
+    for &(_, x, y) in &candidates {
+        if max_corners > 0 && accepted >= max_corners {
+            break;
+        }
+
+        let gx = x / cell;
+        let gy = y / cell;
+        let mut too_close = false;
+        if min_dist > 0.0 {
+            'search: for cy in gy.saturating_sub(1)..=(gy + 1).min(grid_h - 1) {
+                for cx in gx.saturating_sub(1)..=(gx + 1).min(grid_w - 1) {
+                    for &(px, py) in &grid[cy * grid_w + cx] {
+                        let dx = px as f32 - x as f32;
+                        let dy = py as f32 - y as f32;
+                        if dx * dx + dy * dy < min_dist_sq {
+                            too_close = true;
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+        if too_close {
+            continue;
+        }
+
+        grid[gy * grid_w + gx].push((x, y));
+        result.push(x as f32);
+        result.push(y as f32);
+        accepted += 1;
+    }
+
+    result
+}
@@ -1,4 +1,5 @@
 use wasm_bindgen::prelude::*;
+use crate::rounding::RoundMode;
 
 #[wasm_bindgen]
 pub fn calculate_gradients(blurred: &[u8], width: usize, height: usize) -> Vec<i16> {
@@ -18,3 +19,138 @@ pub fn calculate_gradients(blurred: &[u8], width: usize, height: usize) -> Vec<i
 
     result
 }
+
+/// 3x3 Sobel gradients, interleaved as `[gx0, gy0, gx1, gy1, ...]` like
+/// `calculate_gradients`. Border pixels are sampled with clamping so every
+/// pixel gets a gradient.
+#[wasm_bindgen]
+pub fn calculate_sobel_gradients(input: &[u8], width: usize, height: usize) -> Vec<i16> {
+    let size = width * height;
+    if input.len() != size {
+        panic!("Input array size doesn't match width * height");
+    }
+    let mut result = vec![0i16; 2 * size];
+
+    for y in 0..height {
+        let ym = y.saturating_sub(1) * width;
+        let yc = y * width;
+        let yp = (y + 1).min(height - 1) * width;
+        for x in 0..width {
+            let xm = x.saturating_sub(1);
+            let xp = (x + 1).min(width - 1);
+
+            let tl = input[ym + xm] as i16;
+            let tc = input[ym + x] as i16;
+            let tr = input[ym + xp] as i16;
+            let ml = input[yc + xm] as i16;
+            let mr = input[yc + xp] as i16;
+            let bl = input[yp + xm] as i16;
+            let bc = input[yp + x] as i16;
+            let br = input[yp + xp] as i16;
+
+            let gx = (tr + 2 * mr + br) - (tl + 2 * ml + bl);
+            let gy = (bl + 2 * bc + br) - (tl + 2 * tc + tr);
+            let idx = yc + x;
+            result[2 * idx] = gx;
+            result[2 * idx + 1] = gy;
+        }
+    }
+
+    result
+}
+
+/// Sobel gradient orientation in radians, `atan2(gy, gx)` in (-PI, PI].
+/// Returned to JavaScript as a `Float32Array` so sub-degree precision survives:
+///
+/// ```js
+/// const theta = calculate_gradient_orientation(gray, width, height);
+/// const degrees = theta[y * width + x] * 180 / Math.PI;
+/// ```
+#[wasm_bindgen]
+pub fn calculate_gradient_orientation(input: &[u8], width: usize, height: usize) -> Vec<f32> {
+    let gradients = calculate_sobel_gradients(input, width, height);
+    gradients
+        .chunks_exact(2)
+        .map(|g| (g[1] as f32).atan2(g[0] as f32))
+        .collect()
+}
+
+/// Sobel gradient magnitude `sqrt(gx^2 + gy^2)` without rounding, as a `Float32Array`.
+#[wasm_bindgen]
+pub fn calculate_gradient_magnitude(input: &[u8], width: usize, height: usize) -> Vec<f32> {
+    let gradients = calculate_sobel_gradients(input, width, height);
+    gradients
+        .chunks_exact(2)
+        .map(|g| {
+            let gx = g[0] as f32;
+            let gy = g[1] as f32;
+            (gx * gx + gy * gy).sqrt()
+        })
+        .collect()
+}
+
+/// Sobel gradients of an image with everything derived from them, computed
+/// in one pass by `compute_gradients`.
+#[wasm_bindgen]
+pub struct GradientResult {
+    gx: Vec<i16>,
+    gy: Vec<i16>,
+    magnitude: Vec<u8>,
+    direction: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl GradientResult {
+    /// Horizontal Sobel response per pixel.
+    #[wasm_bindgen(getter)]
+    pub fn gx(&self) -> Vec<i16> {
+        self.gx.clone()
+    }
+
+    /// Vertical Sobel response per pixel.
+    #[wasm_bindgen(getter)]
+    pub fn gy(&self) -> Vec<i16> {
+        self.gy.clone()
+    }
+
+    /// `sqrt(gx^2 + gy^2)` rounded with the current `RoundMode` and
+    /// saturated to 255, for display and thresholding;
+    /// `calculate_gradient_magnitude` keeps the full range.
+    #[wasm_bindgen(getter)]
+    pub fn magnitude(&self) -> Vec<u8> {
+        self.magnitude.clone()
+    }
+
+    /// `atan2(gy, gx)` in radians, in (-PI, PI], like `calculate_gradient_orientation`.
+    #[wasm_bindgen(getter)]
+    pub fn direction(&self) -> Vec<f32> {
+        self.direction.clone()
+    }
+}
+
+/// 3x3 Sobel gradients with their magnitude and direction from a single
+/// pass, for callers that need several of them (corner detectors,
+/// non-maximum suppression, stroke width) without recomputing the filter.
+#[wasm_bindgen]
+pub fn compute_gradients(input: &[u8], width: usize, height: usize) -> GradientResult {
+    let gradients = calculate_sobel_gradients(input, width, height);
+    let size = width * height;
+    let mut result = GradientResult {
+        gx: Vec::with_capacity(size),
+        gy: Vec::with_capacity(size),
+        magnitude: Vec::with_capacity(size),
+        direction: Vec::with_capacity(size),
+    };
+
+    let rounding = RoundMode::current();
+    for g in gradients.chunks_exact(2) {
+        let gx = g[0] as f32;
+        let gy = g[1] as f32;
+        result.gx.push(g[0]);
+        result.gy.push(g[1]);
+        result.magnitude.push(rounding.to_u8((gx * gx + gy * gy).sqrt()));
+        result.direction.push(gy.atan2(gx));
+    }
+
+    result
+}
@@ -0,0 +1,260 @@
+use wasm_bindgen::prelude::*;
+use crate::rounding::RoundMode;
+
+/// 256-bin intensity histogram of a grayscale buffer.
+pub(crate) fn histogram(input: &[u8]) -> [u32; 256] {
+    let mut hist = [0u32; 256];
+    for &v in input {
+        hist[v as usize] += 1;
+    }
+    hist
+}
+
+/// Histogram of a 16-bit buffer quantized into `bins` equal-width bins; value
+/// `v` lands in bin `v * bins / 65536`.
+pub(crate) fn histogram_u16(input: &[u16], bins: usize) -> Vec<u32> {
+    let mut hist = vec![0u32; bins];
+    for &v in input {
+        hist[(v as usize * bins) >> 16] += 1;
+    }
+    hist
+}
+
+/// Clips every bin of `hist` at `limit` and spreads the clipped excess evenly
+/// over all bins (the remainder going to the lowest bins), as in CLAHE.
+/// The total count is preserved.
+pub(crate) fn clip_histogram(hist: &mut [u32], limit: u32) {
+    let mut excess = 0u32;
+    for bin in hist.iter_mut() {
+        if *bin > limit {
+            excess += *bin - limit;
+            *bin = limit;
+        }
+    }
+    // Redistribute excess equally
+    let bins = hist.len() as u32;
+    let per_bin = excess / bins;
+    let remainder = (excess % bins) as usize;
+    for (i, bin) in hist.iter_mut().enumerate() {
+        *bin += per_bin;
+        if i < remainder {
+            *bin += 1;
+        }
+    }
+}
+
+/// Histogram-equalization mapping `(cdf - cdf_min) / (total - cdf_min) * 255`,
+/// rounded. `total` is the pixel count of the histogram; when every pixel has
+/// the same value the mapping is the identity.
+pub(crate) fn cdf_to_lut(hist: &[u32], total: u32) -> [u8; 256] {
+    let mut cdf = [0u32; 256];
+    let mut running = 0u32;
+    for (c, &h) in cdf.iter_mut().zip(hist) {
+        running += h;
+        *c = running;
+    }
+
+    // Find min CDF value (first non-zero)
+    let cdf_min = cdf.iter().copied().find(|&v| v > 0).unwrap_or(0);
+    let denom = total as f32 - cdf_min as f32;
+
+    let rounding = RoundMode::current();
+    let mut lut = [0u8; 256];
+    for (i, out) in lut.iter_mut().enumerate() {
+        *out = if denom > 0.0 {
+            rounding.to_u8((cdf[i] as f32 - cdf_min as f32) / denom * 255.0)
+        } else {
+            i as u8
+        };
+    }
+    lut
+}
+
+/// Otsu threshold of a histogram: the level `t` that maximizes the
+/// between-class variance of `{v <= t}` and `{v > t}`. Returns 0 for an
+/// empty histogram; ties keep the lowest level.
+pub(crate) fn otsu_from_histogram(hist: &[u32; 256]) -> u8 {
+    let total: u64 = hist.iter().map(|&c| c as u64).sum();
+    if total == 0 {
+        return 0;
+    }
+    let weighted_total: f64 = hist.iter().enumerate().map(|(v, &c)| v as f64 * c as f64).sum();
+
+    let mut best_level = 0u8;
+    let mut best_variance = -1.0f64;
+    let mut below = 0u64;
+    let mut weighted_below = 0.0f64;
+    for (level, &count) in hist.iter().enumerate() {
+        below += count as u64;
+        weighted_below += level as f64 * count as f64;
+        let above = total - below;
+        if below == 0 || above == 0 {
+            continue;
+        }
+        let mean_below = weighted_below / below as f64;
+        let mean_above = (weighted_total - weighted_below) / above as f64;
+        let diff = mean_below - mean_above;
+        let variance = below as f64 * above as f64 * diff * diff;
+        if variance > best_variance {
+            best_variance = variance;
+            best_level = level as u8;
+        }
+    }
+
+    best_level
+}
+
+/// Global Otsu threshold. Pixels above the returned level form the bright
+/// class, matching OpenCV's `THRESH_OTSU`.
+#[wasm_bindgen]
+pub fn otsu_threshold(input: &[u8], width: usize, height: usize) -> u8 {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    otsu_from_histogram(&histogram(input))
+}
+
+/// Polarity of a document: true for dark content on a light background,
+/// false for the reverse. The background is taken to be the larger of the
+/// two Otsu classes; ties count as dark on light, the common case.
+///
+/// The threshold functions (`adaptive_threshold`, `bradley_threshold`, ...)
+/// turn dark content into 0 with `invert` unset, so passing
+/// `!detect_polarity(..)` as `invert` gives content 0 and background 255
+/// for pages of either polarity.
+#[wasm_bindgen]
+pub fn detect_polarity(input: &[u8], width: usize, height: usize) -> bool {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let hist = histogram(input);
+    let level = otsu_from_histogram(&hist) as usize;
+    let dark: u64 = hist[..=level].iter().map(|&c| c as u64).sum();
+    let light: u64 = hist[level + 1..].iter().map(|&c| c as u64).sum();
+    light >= dark
+}
+
+/// Histogram of a 16-bit grayscale image in `bins` equal-width bins covering
+/// 0-65535, so high-bit-depth data gets a histogram of manageable size and
+/// with enough counts per bin to be meaningful. `bins = 256` matches taking
+/// the top byte of every pixel.
+///
+/// # Returns
+/// `bins` counts; bin `i` holds values `i * 65536 / bins` up to (excluding)
+/// `(i + 1) * 65536 / bins`
+#[wasm_bindgen]
+pub fn histogram_u16_binned(input: &[u16], width: usize, height: usize, bins: usize) -> Vec<u32> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if bins == 0 || bins > 65536 {
+        panic!("Bin count must be between 1 and 65536");
+    }
+
+    histogram_u16(input, bins)
+}
+
+/// Dominant intensity clusters via 1D k-means on the histogram.
+/// Centers are seeded at evenly spaced quantiles of the pixel distribution and
+/// refined with weighted Lloyd iterations over the 256 bins, so the cost is
+/// independent of image size after the histogram pass.
+///
+/// # Arguments
+/// * `k` - Number of clusters to find
+///
+/// # Returns
+/// Cluster centers ordered by population (most prominent first). Fewer than
+/// `k` values are returned when the image has fewer distinct intensities.
+#[wasm_bindgen]
+pub fn dominant_intensities(input: &[u8], width: usize, height: usize, k: usize) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+    if k == 0 || pixel_count == 0 {
+        return Vec::new();
+    }
+
+    let hist = histogram(input);
+    let distinct = hist.iter().filter(|&&c| c > 0).count();
+    let k = k.min(distinct);
+
+    // Seed centers at the midpoints of k equal-population slices
+    let mut centers = Vec::with_capacity(k);
+    let mut cumulative = 0u64;
+    let mut slice = 0;
+    for (value, &count) in hist.iter().enumerate() {
+        cumulative += count as u64;
+        while slice < k && cumulative * (k as u64) * 2 >= (2 * slice as u64 + 1) * pixel_count as u64 {
+            centers.push(value as f64);
+            slice += 1;
+        }
+    }
+
+    // Quantile seeds collapse onto one bin when a value dominates the image;
+    // replace duplicates with the occupied bins farthest from existing centers
+    centers.dedup();
+    while centers.len() < k {
+        let mut best_value = 0;
+        let mut best_dist = -1.0;
+        for (value, &count) in hist.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let v = value as f64;
+            let dist = centers.iter().map(|&c| (v - c).abs()).fold(f64::MAX, f64::min);
+            if dist > best_dist {
+                best_dist = dist;
+                best_value = value;
+            }
+        }
+        centers.push(best_value as f64);
+    }
+
+    // Weighted Lloyd iterations over histogram bins
+    let mut populations = vec![0u64; k];
+    for _ in 0..100 {
+        let mut sums = vec![0f64; k];
+        populations.iter_mut().for_each(|p| *p = 0);
+
+        for (value, &count) in hist.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let v = value as f64;
+            let mut best = 0;
+            for c in 1..k {
+                if (v - centers[c]).abs() < (v - centers[best]).abs() {
+                    best = c;
+                }
+            }
+            sums[best] += v * count as f64;
+            populations[best] += count as u64;
+        }
+
+        let mut moved = false;
+        for c in 0..k {
+            if populations[c] > 0 {
+                let updated = sums[c] / populations[c] as f64;
+                if (updated - centers[c]).abs() > 1e-3 {
+                    moved = true;
+                }
+                centers[c] = updated;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+
+    let mut clusters: Vec<(u64, u8)> = populations
+        .iter()
+        .zip(centers.iter())
+        .filter(|(&p, _)| p > 0)
+        .map(|(&p, &c)| (p, c.round().clamp(0.0, 255.0) as u8))
+        .collect();
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.0));
+
+    clusters.into_iter().map(|(_, c)| c).collect()
+}
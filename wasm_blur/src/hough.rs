@@ -0,0 +1,332 @@
+use wasm_bindgen::prelude::*;
+use crate::canny::canny_edge_detector_full;
+use crate::downscale::downscale_by;
+use crate::warp::warp_affine;
+
+/// Unit normal of the edge curve through (x, y), from the principal axis of
+/// the edge pixels in the surrounding 5x5 window. `None` for isolated pixels
+/// and blobs without a dominant direction.
+fn edge_normal(edges: &[u8], width: usize, height: usize, x: usize, y: usize) -> Option<(f32, f32)> {
+    let (mut n, mut sx, mut sy, mut sxx, mut syy, mut sxy) = (0.0f32, 0.0, 0.0, 0.0, 0.0, 0.0);
+    for ny in y.saturating_sub(2)..(y + 3).min(height) {
+        for nx in x.saturating_sub(2)..(x + 3).min(width) {
+            if edges[ny * width + nx] == 0 {
+                continue;
+            }
+            let (dx, dy) = (nx as f32 - x as f32, ny as f32 - y as f32);
+            n += 1.0;
+            sx += dx;
+            sy += dy;
+            sxx += dx * dx;
+            syy += dy * dy;
+            sxy += dx * dy;
+        }
+    }
+    if n < 3.0 {
+        return None;
+    }
+
+    let cxx = sxx / n - (sx / n) * (sx / n);
+    let cyy = syy / n - (sy / n) * (sy / n);
+    let cxy = sxy / n - (sx / n) * (sy / n);
+    // Elongation check: the two eigenvalues must clearly differ
+    let spread = ((cxx - cyy) * (cxx - cyy) + 4.0 * cxy * cxy).sqrt();
+    if spread < 0.25 * (cxx + cyy) {
+        return None;
+    }
+    // The tangent is the major axis; the normal is perpendicular to it
+    let tangent = 0.5 * (2.0 * cxy).atan2(cxx - cyy);
+    Some((-tangent.sin(), tangent.cos()))
+}
+
+/// Radius in `min_radius..=max_radius` that most edge pixels lie at around
+/// (cx, cy), with its vote count. Each edge pixel supports the two integer
+/// radii around its distance, so a rasterized circle isn't split between
+/// neighbouring bins.
+fn radius_support(
+    edge_points: &[(usize, usize)],
+    cx: usize,
+    cy: usize,
+    min_radius: usize,
+    max_radius: usize,
+    radii: &mut [u32],
+) -> (usize, u32) {
+    radii.fill(0);
+    for &(x, y) in edge_points {
+        let d = (x as f32 - cx as f32).hypot(y as f32 - cy as f32);
+        let lower = d.floor() as usize;
+        for r in [lower, lower + 1] {
+            if (min_radius..=max_radius).contains(&r) {
+                radii[r] += 1;
+            }
+        }
+    }
+    let (r, &votes) = radii
+        .iter()
+        .enumerate()
+        .skip(min_radius)
+        .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(&a.0)))
+        .unwrap();
+    (r, votes)
+}
+
+/// Hough circle transform on a binary edge map (e.g. from
+/// `canny_edge_detector_full`), for round stamps, seals and punch holes.
+///
+/// Every edge pixel votes for circle centers along its normal, in both
+/// directions and at every radius in `min_radius..=max_radius`; the normal is
+/// estimated from the neighbouring edge pixels. Centers that are local maxima
+/// of this 2D accumulator with at least `threshold` votes are then moved by up
+/// to one pixel and given the radius that most edge pixels lie at (within one
+/// pixel), and kept when that radius is supported by at least `threshold` edge
+/// pixels. Splitting center and radius search keeps memory at `width * height`
+/// counters instead of a 3D accumulator per radius. Circles whose centers lie
+/// within `min_radius` of a stronger circle are dropped.
+///
+/// # Returns
+/// Flat `[cx, cy, r, votes, ...]`, strongest first
+#[wasm_bindgen]
+pub fn hough_circles(
+    edges: &[u8],
+    width: usize,
+    height: usize,
+    min_radius: usize,
+    max_radius: usize,
+    threshold: u32,
+) -> Vec<f32> {
+    let pixel_count = width * height;
+    if edges.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+    if min_radius == 0 || min_radius > max_radius {
+        panic!("Radius range must satisfy 0 < min_radius <= max_radius");
+    }
+
+    let edge_points: Vec<(usize, usize)> = (0..pixel_count)
+        .filter(|&i| edges[i] != 0)
+        .map(|i| (i % width, i / width))
+        .collect();
+
+    // Stage 1: center votes
+    let mut centers = vec![0u32; pixel_count];
+    for &(x, y) in &edge_points {
+        let Some((nx, ny)) = edge_normal(edges, width, height, x, y) else { continue };
+        for sign in [-1.0f32, 1.0] {
+            for r in min_radius..=max_radius {
+                let cx = (x as f32 + sign * nx * r as f32).round();
+                let cy = (y as f32 + sign * ny * r as f32).round();
+                if cx < 0.0 || cy < 0.0 || cx >= width as f32 || cy >= height as f32 {
+                    break;
+                }
+                centers[cy as usize * width + cx as usize] += 1;
+            }
+        }
+    }
+
+    let mut candidates: Vec<(u32, usize, usize)> = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let votes = centers[y * width + x];
+            if votes < threshold || votes == 0 {
+                continue;
+            }
+            let is_max = (y.saturating_sub(1)..(y + 2).min(height))
+                .flat_map(|ny| (x.saturating_sub(1)..(x + 2).min(width)).map(move |nx| (nx, ny)))
+                .all(|(nx, ny)| {
+                    let other = centers[ny * width + nx];
+                    // Ties go to the first pixel in scan order
+                    other < votes || (other == votes && (ny, nx) >= (y, x))
+                });
+            if is_max {
+                candidates.push((votes, x, y));
+            }
+        }
+    }
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.0));
+
+    // Stage 2: best supported radius per center. Rounding along the normals
+    // smears the center votes over neighbouring pixels, so the 3x3
+    // neighbourhood of each peak is searched for the sharpest radius
+    let mut circles: Vec<(f32, f32, f32, u32)> = Vec::new();
+    let mut radii = vec![0u32; max_radius + 2];
+    for &(_, cx, cy) in &candidates {
+        if circles
+            .iter()
+            .any(|c| (c.0 - cx as f32).hypot(c.1 - cy as f32) < min_radius as f32)
+        {
+            continue;
+        }
+
+        let mut best = (0u32, cx, cy, 0usize);
+        for y in cy.saturating_sub(1)..(cy + 2).min(height) {
+            for x in cx.saturating_sub(1)..(cx + 2).min(width) {
+                let (r, votes) = radius_support(&edge_points, x, y, min_radius, max_radius, &mut radii);
+                // Ties keep the peak itself, then the first pixel in scan order
+                if votes > best.0 || (votes == best.0 && (x, y) == (cx, cy)) {
+                    best = (votes, x, y, r);
+                }
+            }
+        }
+        let (votes, x, y, r) = best;
+        if votes >= threshold {
+            circles.push((x as f32, y as f32, r as f32, votes));
+        }
+    }
+    circles.sort_by_key(|c| std::cmp::Reverse(c.3));
+
+    circles
+        .iter()
+        .flat_map(|&(cx, cy, r, votes)| [cx, cy, r, votes as f32])
+        .collect()
+}
+
+/// Longest side the skew is measured at; `deskew` downscales larger images.
+const DESKEW_DETECTION_SIZE: usize = 512;
+
+/// A straightened image together with the skew that was removed.
+#[wasm_bindgen]
+pub struct DeskewResult {
+    image: Vec<u8>,
+    angle: f32,
+}
+
+#[wasm_bindgen]
+impl DeskewResult {
+    /// The rotated image, same size as the input.
+    #[wasm_bindgen(getter)]
+    pub fn image(&self) -> Vec<u8> {
+        self.image.clone()
+    }
+
+    /// Detected skew in degrees; positive when lines descend to the right
+    /// (the content was rotated clockwise). The image was rotated by `-angle`.
+    #[wasm_bindgen(getter)]
+    pub fn angle(&self) -> f32 {
+        self.angle
+    }
+}
+
+/// Dominant line angle of an edge map within `-max_angle..=max_angle`
+/// degrees, in 0.1 degree steps. Each edge pixel votes for the Hough line
+/// through it at every candidate angle; an angle scores the sum of squared
+/// votes over its accumulator row, which peaks where many edge pixels share
+/// lines (text baselines, table rules, page edges).
+fn dominant_skew(edges: &[u8], width: usize, height: usize, max_angle: f32) -> f32 {
+    let steps = (max_angle * 10.0).round() as i32;
+    let diagonal = (width as f32).hypot(height as f32).ceil() as usize;
+    let points: Vec<(f32, f32)> = (0..width * height)
+        .filter(|&i| edges[i] != 0)
+        .map(|i| ((i % width) as f32, (i / width) as f32))
+        .collect();
+
+    let mut best = (0.0f32, -1.0f64);
+    let mut row = vec![0u32; 2 * diagonal + 1];
+    for step in -steps..=steps {
+        let angle = step as f32 / 10.0;
+        let (sin, cos) = angle.to_radians().sin_cos();
+        row.iter_mut().for_each(|v| *v = 0);
+        for &(x, y) in &points {
+            // Distance of the line through (x, y) at this angle from the origin
+            let rho = (y * cos - x * sin).round() as isize + diagonal as isize;
+            row[rho as usize] += 1;
+        }
+        let score: f64 = row.iter().map(|&v| (v as f64) * (v as f64)).sum();
+        // Ties keep the angle closest to zero
+        if score > best.1 || (score == best.1 && angle.abs() < best.0.abs()) {
+            best = (angle, score);
+        }
+    }
+
+    best.0
+}
+
+/// Straightens a skewed scan. The skew is detected on a copy downscaled to
+/// at most 512 pixels on its longer side: Canny edges, then a Hough
+/// accumulator limited to angles within `max_angle_deg` of horizontal (see
+/// the `angle` of the result). The full-resolution image is then rotated
+/// about its center to correct it, with bilinear sampling; corners uncovered
+/// by the rotation are filled white.
+///
+/// # Arguments
+/// * `max_angle_deg` - Largest skew considered, in degrees (0 < x < 45)
+#[wasm_bindgen]
+pub fn deskew(input: &[u8], width: usize, height: usize, max_angle_deg: f32) -> DeskewResult {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if !(max_angle_deg > 0.0 && max_angle_deg < 45.0) {
+        panic!("Maximum angle must be between 0 and 45 degrees");
+    }
+
+    let factor = width.max(height).div_ceil(DESKEW_DETECTION_SIZE).max(1);
+    let (small_width, small_height) = (width / factor, height / factor);
+    if small_width < 3 || small_height < 3 {
+        return DeskewResult { image: input.to_vec(), angle: 0.0 };
+    }
+    let small = downscale_by(input, width, height, factor);
+    let edges = canny_edge_detector_full(&small, small_width, small_height, 50.0, 150.0, 5, 0.0, false, false, 0);
+    let angle = dominant_skew(&edges, small_width, small_height, max_angle_deg);
+    if angle == 0.0 {
+        return DeskewResult { image: input.to_vec(), angle };
+    }
+
+    // Rotate by -angle about the center: x' = R(-angle) * (x - c) + c
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (cx, cy) = ((width as f32 - 1.0) / 2.0, (height as f32 - 1.0) / 2.0);
+    let matrix = [cos, sin, cx - cos * cx - sin * cy, -sin, cos, cy + sin * cx - cos * cy];
+    let image = warp_affine(input, width, height, &matrix, width, height, 255);
+    DeskewResult { image, angle }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Darkest row of column `x` within `rows`.
+    fn darkest_row(image: &[u8], width: usize, x: usize, rows: std::ops::Range<usize>) -> usize {
+        rows.min_by_key(|&y| image[y * width + x]).unwrap()
+    }
+
+    #[test]
+    fn test_hough_circles_rasterized_circle() {
+        let (width, height) = (100, 80);
+        let edges: Vec<u8> = (0..width * height)
+            .map(|i| {
+                let d = ((i % width) as f32 - 55.0).hypot((i / width) as f32 - 38.0);
+                if (d - 20.0).abs() < 0.5 { 255 } else { 0 }
+            })
+            .collect();
+
+        let circles = hough_circles(&edges, width, height, 10, 30, 30);
+        assert_eq!(circles.len(), 4);
+        assert_eq!(circles[..3], [55.0, 38.0, 20.0]);
+        // Every edge pixel supports the radius
+        assert_eq!(circles[3], 112.0);
+    }
+
+    #[test]
+    fn test_deskew_rotated_lines() {
+        // Horizontal 3 px rules every 20 rows, rotated clockwise by 3 degrees
+        let (width, height) = (200, 200);
+        let mut lines = vec![255u8; width * height];
+        for y in (40..170).step_by(20) {
+            for row in lines[y * width..(y + 3) * width].chunks_mut(width) {
+                row[20..180].fill(0);
+            }
+        }
+        let (sin, cos) = 3.0f32.to_radians().sin_cos();
+        let (cx, cy) = ((width as f32 - 1.0) / 2.0, (height as f32 - 1.0) / 2.0);
+        let matrix = [cos, -sin, cx - cos * cx + sin * cy, sin, cos, cy - sin * cx - cos * cy];
+        let skewed = warp_affine(&lines, width, height, &matrix, width, height, 255);
+        assert!(darkest_row(&skewed, width, 60, 90..110) < darkest_row(&skewed, width, 140, 90..110));
+
+        let result = deskew(&skewed, width, height, 10.0);
+        assert!((result.angle() - 3.0).abs() <= 0.2, "angle {}", result.angle());
+        let image = result.image();
+        for y in (40..170).step_by(20) {
+            let left = darkest_row(&image, width, 60, y - 5..y + 8);
+            let right = darkest_row(&image, width, 140, y - 5..y + 8);
+            assert!(left.abs_diff(right) <= 1, "line {y}: rows {left} and {right}");
+        }
+    }
+}
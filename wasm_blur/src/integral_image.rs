@@ -0,0 +1,157 @@
+use wasm_bindgen::prelude::*;
+
+/// Summed-area tables of an image and (optionally) its square, for O(1)
+/// window sums, means and variances.
+pub(crate) struct IntegralImage {
+    width: usize,
+    height: usize,
+    // (width + 1) x (height + 1), first row and column are zero
+    sums: Vec<u64>,
+    // Same layout over squared values; empty unless built `with_squares`
+    squares: Vec<u64>,
+}
+
+impl IntegralImage {
+    pub(crate) fn new(input: &[u8], width: usize, height: usize, with_squares: bool) -> IntegralImage {
+        let stride = width + 1;
+        let mut sums = vec![0u64; stride * (height + 1)];
+        let mut squares = if with_squares { vec![0u64; stride * (height + 1)] } else { Vec::new() };
+
+        for y in 0..height {
+            let mut row_sum = 0u64;
+            let mut row_sq = 0u64;
+            for x in 0..width {
+                let v = input[y * width + x] as u64;
+                row_sum += v;
+                row_sq += v * v;
+                let idx = (y + 1) * stride + x + 1;
+                sums[idx] = sums[idx - stride] + row_sum;
+                if with_squares {
+                    squares[idx] = squares[idx - stride] + row_sq;
+                }
+            }
+        }
+
+        IntegralImage { width, height, sums, squares }
+    }
+
+    /// Table over the per-pixel products `a * b` of two images (no squares),
+    /// for window covariances.
+    pub(crate) fn from_products(a: &[u8], b: &[u8], width: usize, height: usize) -> IntegralImage {
+        let stride = width + 1;
+        let mut sums = vec![0u64; stride * (height + 1)];
+        for y in 0..height {
+            let mut row_sum = 0u64;
+            for x in 0..width {
+                row_sum += a[y * width + x] as u64 * b[y * width + x] as u64;
+                let idx = (y + 1) * stride + x + 1;
+                sums[idx] = sums[idx - stride] + row_sum;
+            }
+        }
+        IntegralImage { width, height, sums, squares: Vec::new() }
+    }
+
+    #[inline]
+    fn rect(table: &[u64], stride: usize, x0: usize, y0: usize, x1: usize, y1: usize) -> u64 {
+        table[y1 * stride + x1] + table[y0 * stride + x0] - table[y0 * stride + x1] - table[y1 * stride + x0]
+    }
+
+    /// Clipped window of radius `half` around (x, y) as end-exclusive bounds.
+    #[inline]
+    fn window(&self, x: usize, y: usize, half: usize) -> (usize, usize, usize, usize) {
+        (
+            x.saturating_sub(half),
+            y.saturating_sub(half),
+            (x + half + 1).min(self.width),
+            (y + half + 1).min(self.height),
+        )
+    }
+
+    /// Sum and pixel count of the (clipped) window of radius `half` around (x, y).
+    #[inline]
+    pub(crate) fn window_sum(&self, x: usize, y: usize, half: usize) -> (u64, u64) {
+        let (x0, y0, x1, y1) = self.window(x, y, half);
+        let count = ((x1 - x0) * (y1 - y0)) as u64;
+        (Self::rect(&self.sums, self.width + 1, x0, y0, x1, y1), count)
+    }
+
+    /// Mean and population variance of the (clipped) window of radius `half`
+    /// around (x, y). Requires the table to be built `with_squares`.
+    #[inline]
+    pub(crate) fn window_mean_variance(&self, x: usize, y: usize, half: usize) -> (f64, f64) {
+        let (x0, y0, x1, y1) = self.window(x, y, half);
+        let stride = self.width + 1;
+        let n = ((x1 - x0) * (y1 - y0)) as f64;
+        let sum = Self::rect(&self.sums, stride, x0, y0, x1, y1) as f64;
+        let sum_sq = Self::rect(&self.squares, stride, x0, y0, x1, y1) as f64;
+        let mean = sum / n;
+        (mean, (sum_sq / n - mean * mean).max(0.0))
+    }
+}
+
+/// Summed-area table of the image, `(width + 1) * (height + 1)` values with a
+/// leading zero row and column, so the sum of the rectangle [x0, x1) x [y0, y1)
+/// is `I[y1][x1] - I[y0][x1] - I[y1][x0] + I[y0][x0]`.
+/// Returned as a `Float64Array`, which is exact for any realistic image size.
+#[wasm_bindgen]
+pub fn integral_image(input: &[u8], width: usize, height: usize) -> Vec<f64> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    IntegralImage::new(input, width, height, false)
+        .sums
+        .iter()
+        .map(|&v| v as f64)
+        .collect()
+}
+
+/// Per-pixel population variance over the `window` x `window` square
+/// centered on each pixel (clipped at the borders), from integral images of
+/// the image and its square, so the cost per pixel is constant for any
+/// window size. A small window (5-9) makes a cheap local-contrast or focus
+/// map; Niblack/Sauvola-style thresholds use larger ones.
+///
+/// # Returns
+/// `Float32Array` of variances in squared grey levels
+#[wasm_bindgen]
+pub fn local_variance(input: &[u8], width: usize, height: usize, window: usize) -> Vec<f32> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let half = window / 2;
+    let integral = IntegralImage::new(input, width, height, true);
+    let mut output = vec![0.0f32; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            output[y * width + x] = integral.window_mean_variance(x, y, half).1 as f32;
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_variance_matches_direct() {
+        let (width, height, window) = (13, 9, 5);
+        let input: Vec<u8> = (0..width * height).map(|i| ((i * 97 + i / 7 * 31) % 256) as u8).collect();
+        let variances = local_variance(&input, width, height, window);
+
+        for y in 0..height {
+            for x in 0..width {
+                let values: Vec<f64> = (y.saturating_sub(2)..(y + 3).min(height))
+                    .flat_map(|ny| (x.saturating_sub(2)..(x + 3).min(width)).map(move |nx| (nx, ny)))
+                    .map(|(nx, ny)| input[ny * width + nx] as f64)
+                    .collect();
+                let n = values.len() as f64;
+                let mean = values.iter().sum::<f64>() / n;
+                let expected = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n;
+                assert!((variances[y * width + x] as f64 - expected).abs() < 1e-2);
+            }
+        }
+    }
+}
@@ -8,6 +8,45 @@ pub mod clahe;
 pub mod adaptive_thresh;
 pub mod morphology;
 pub mod unsharp_mask;
+pub mod fast_corners;
+pub mod good_features;
+pub mod line_segments;
+pub mod line_removal;
+pub mod histogram;
+pub mod statistics;
+pub mod texture;
+pub mod lut;
+pub mod convolution;
+pub mod border;
+pub mod stylize;
+pub mod downscale;
+pub mod pyramid;
+pub mod mask_ops;
+pub mod visualize;
+pub mod tiled;
+pub mod memory;
+pub mod integral_image;
+pub mod progress;
+pub mod roi;
+pub mod tiling;
+pub mod rounding;
+pub mod warp;
+pub mod dither;
+pub mod retinex;
+pub mod diffusion;
+pub mod swt;
+pub mod mser;
+pub mod hough;
+pub mod quad;
+pub mod chain_code;
+pub mod moments;
+pub mod color;
+pub mod rolling_ball;
+pub mod vignette;
+pub mod quality;
+pub mod batch;
+pub mod chessboard;
+pub mod pipeline;
 
 // Re-export the blur function from gaussian_blur module for backward compatibility
 pub use gaussian_blur::blur;
\ No newline at end of file
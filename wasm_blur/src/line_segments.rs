@@ -0,0 +1,203 @@
+use wasm_bindgen::prelude::*;
+use crate::gradient_calculation::calculate_sobel_gradients;
+use std::f32::consts::PI;
+
+// Angle tolerance for region growing (22.5 degrees, as in LSD)
+const ANGLE_TOLERANCE: f32 = PI / 8.0;
+// Minimum Sobel magnitude for a pixel to take part in a region.
+// LSD uses q / sin(tau) ~= 5.2 on a 2x2 gradient; Sobel is 4x larger.
+const MIN_MAGNITUDE: f32 = 21.0;
+// Minimum fraction of the fitted rectangle that must be covered by region pixels
+const MIN_DENSITY: f32 = 0.7;
+// Regions with fewer pixels than this are ignored
+const MIN_REGION_PIXELS: usize = 5;
+// Number of magnitude buckets used to order seeds strongest-first
+const MAGNITUDE_BINS: usize = 1024;
+
+#[inline]
+fn angle_diff(a: f32, b: f32) -> f32 {
+    let mut d = a - b;
+    while d <= -PI {
+        d += 2.0 * PI;
+    }
+    while d > PI {
+        d -= 2.0 * PI;
+    }
+    d.abs()
+}
+
+/// Line segment detection in the spirit of LSD (Grompone von Gioi et al.).
+/// Pixels are grouped into line-support regions by growing from the strongest
+/// gradients while the level-line orientation stays within 22.5 degrees of
+/// the region's mean orientation. Each region is approximated by a rectangle
+/// whose principal axis gives the segment endpoints. Regions that cover less
+/// than 70% of their rectangle are rejected; this density test stands in for
+/// LSD's a-contrario validation.
+///
+/// # Arguments
+/// * `min_length` - Segments shorter than this (in pixels) are dropped
+///
+/// # Returns
+/// Flat `[x1, y1, x2, y2, ...]` endpoints in pixel coordinates
+#[wasm_bindgen]
+pub fn detect_line_segments(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    min_length: f32,
+) -> Vec<f32> {
+    let size = width * height;
+    if input.len() != size {
+        panic!("Input array size doesn't match width * height");
+    }
+    if width < 3 || height < 3 {
+        return Vec::new();
+    }
+
+    let gradients = calculate_sobel_gradients(input, width, height);
+
+    // Magnitude and level-line angle (perpendicular to the gradient)
+    let mut magnitude = vec![0.0f32; size];
+    let mut angle = vec![0.0f32; size];
+    let mut max_mag = 0.0f32;
+    for i in 0..size {
+        let gx = gradients[2 * i] as f32;
+        let gy = gradients[2 * i + 1] as f32;
+        let mag = (gx * gx + gy * gy).sqrt();
+        magnitude[i] = mag;
+        angle[i] = gx.atan2(-gy);
+        if mag > max_mag {
+            max_mag = mag;
+        }
+    }
+    if max_mag < MIN_MAGNITUDE {
+        return Vec::new();
+    }
+
+    // Pseudo-order pixels by magnitude with a bucket sort (strongest first).
+    // Image borders are excluded since their gradients are unreliable.
+    let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); MAGNITUDE_BINS];
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let idx = y * width + x;
+            let mag = magnitude[idx];
+            if mag >= MIN_MAGNITUDE {
+                let bin = ((mag / max_mag) * (MAGNITUDE_BINS - 1) as f32) as usize;
+                buckets[bin].push(idx);
+            }
+        }
+    }
+
+    let mut used = vec![false; size];
+    let mut region: Vec<usize> = Vec::new();
+    let mut segments = Vec::new();
+
+    for bucket in buckets.iter().rev() {
+        for &seed in bucket {
+            if used[seed] {
+                continue;
+            }
+
+            // Grow the line-support region
+            region.clear();
+            region.push(seed);
+            used[seed] = true;
+            let mut sum_cos = angle[seed].cos();
+            let mut sum_sin = angle[seed].sin();
+            let mut region_angle = angle[seed];
+
+            let mut head = 0;
+            while head < region.len() {
+                let idx = region[head];
+                head += 1;
+                let x = idx % width;
+                let y = idx / width;
+                for dy in -1isize..=1 {
+                    for dx in -1isize..=1 {
+                        let nx = x as isize + dx;
+                        let ny = y as isize + dy;
+                        if nx < 1 || ny < 1 || nx >= (width - 1) as isize || ny >= (height - 1) as isize {
+                            continue;
+                        }
+                        let nidx = ny as usize * width + nx as usize;
+                        if used[nidx] || magnitude[nidx] < MIN_MAGNITUDE {
+                            continue;
+                        }
+                        if angle_diff(angle[nidx], region_angle) <= ANGLE_TOLERANCE {
+                            used[nidx] = true;
+                            region.push(nidx);
+                            sum_cos += angle[nidx].cos();
+                            sum_sin += angle[nidx].sin();
+                            region_angle = sum_sin.atan2(sum_cos);
+                        }
+                    }
+                }
+            }
+
+            if region.len() < MIN_REGION_PIXELS {
+                continue;
+            }
+
+            // Magnitude-weighted centroid
+            let mut sum_w = 0.0f32;
+            let mut cx = 0.0f32;
+            let mut cy = 0.0f32;
+            for &idx in &region {
+                let w = magnitude[idx];
+                cx += (idx % width) as f32 * w;
+                cy += (idx / width) as f32 * w;
+                sum_w += w;
+            }
+            cx /= sum_w;
+            cy /= sum_w;
+
+            // Principal axis from the weighted second moments
+            let mut sxx = 0.0f32;
+            let mut syy = 0.0f32;
+            let mut sxy = 0.0f32;
+            for &idx in &region {
+                let w = magnitude[idx];
+                let dx = (idx % width) as f32 - cx;
+                let dy = (idx / width) as f32 - cy;
+                sxx += w * dx * dx;
+                syy += w * dy * dy;
+                sxy += w * dx * dy;
+            }
+            let theta = 0.5 * (2.0 * sxy).atan2(sxx - syy);
+            let (dir_y, dir_x) = theta.sin_cos();
+
+            // Extent along and across the axis
+            let mut l_min = f32::MAX;
+            let mut l_max = f32::MIN;
+            let mut w_min = f32::MAX;
+            let mut w_max = f32::MIN;
+            for &idx in &region {
+                let dx = (idx % width) as f32 - cx;
+                let dy = (idx / width) as f32 - cy;
+                let l = dx * dir_x + dy * dir_y;
+                let w = -dx * dir_y + dy * dir_x;
+                l_min = l_min.min(l);
+                l_max = l_max.max(l);
+                w_min = w_min.min(w);
+                w_max = w_max.max(w);
+            }
+
+            let length = l_max - l_min;
+            if length < min_length {
+                continue;
+            }
+            // Rectangle area in pixels; the +1 accounts for pixel extent
+            let rect_area = (length + 1.0) * (w_max - w_min + 1.0);
+            if (region.len() as f32) < MIN_DENSITY * rect_area {
+                continue;
+            }
+
+            segments.push(cx + l_min * dir_x);
+            segments.push(cy + l_min * dir_y);
+            segments.push(cx + l_max * dir_x);
+            segments.push(cy + l_max * dir_y);
+        }
+    }
+
+    segments
+}
@@ -0,0 +1,107 @@
+use wasm_bindgen::prelude::*;
+use crate::rounding::RoundMode;
+
+/// Maps every pixel through a caller-supplied 256-entry lookup table.
+/// Covers gamma, brightness/contrast, posterize, invert and arbitrary curves:
+/// compute the curve once in JS and apply it here.
+#[wasm_bindgen]
+pub fn apply_lut(input: &[u8], width: usize, height: usize, lut: &[u8]) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if lut.len() != 256 {
+        panic!("LUT must have exactly 256 entries");
+    }
+
+    // Copy into a fixed-size table so the lookups below need no bounds checks
+    let mut table = [0u8; 256];
+    table.copy_from_slice(lut);
+
+    input.iter().map(|&v| table[v as usize]).collect()
+}
+
+/// 256-entry LUT for `apply_lut` from a curves-style control polygon:
+/// `control_points` is `[x0, y0, x1, y1, ...]` in 0..=255 with strictly
+/// increasing x. The curve is a monotone cubic (Fritsch–Carlson) through
+/// the points, so it never overshoots them and never reverses direction
+/// between them: increasing points give an increasing curve with no tonal
+/// inversions. Levels before the first or after the last point hold that
+/// point's output.
+///
+/// ```js
+/// const lut = build_tone_curve(new Float32Array([0, 0, 64, 40, 192, 220, 255, 255]));
+/// const out = apply_lut(gray, w, h, lut);
+/// ```
+#[wasm_bindgen]
+pub fn build_tone_curve(control_points: &[f32]) -> Vec<u8> {
+    if !control_points.len().is_multiple_of(2) || control_points.len() < 4 {
+        panic!("Tone curve needs at least two (x, y) control points");
+    }
+    let xs: Vec<f32> = control_points.iter().step_by(2).copied().collect();
+    let ys: Vec<f32> = control_points.iter().skip(1).step_by(2).copied().collect();
+    if xs.windows(2).any(|w| w[1] <= w[0]) {
+        panic!("Control point x values must be strictly increasing");
+    }
+
+    // Secant slopes, then tangents limited so each segment stays monotone
+    let n = xs.len();
+    let secants: Vec<f32> = (0..n - 1).map(|k| (ys[k + 1] - ys[k]) / (xs[k + 1] - xs[k])).collect();
+    let mut tangents = vec![0.0f32; n];
+    tangents[0] = secants[0];
+    tangents[n - 1] = secants[n - 2];
+    for k in 1..n - 1 {
+        if secants[k - 1] * secants[k] > 0.0 {
+            tangents[k] = (secants[k - 1] + secants[k]) / 2.0;
+        }
+    }
+    for k in 0..n - 1 {
+        if secants[k] == 0.0 {
+            tangents[k] = 0.0;
+            tangents[k + 1] = 0.0;
+            continue;
+        }
+        let a = tangents[k] / secants[k];
+        let b = tangents[k + 1] / secants[k];
+        let norm = a * a + b * b;
+        if norm > 9.0 {
+            let t = 3.0 / norm.sqrt();
+            tangents[k] = t * a * secants[k];
+            tangents[k + 1] = t * b * secants[k];
+        }
+    }
+
+    let rounding = RoundMode::current();
+    (0..256)
+        .map(|level| {
+            let x = level as f32;
+            let value = if x <= xs[0] {
+                ys[0]
+            } else if x >= xs[n - 1] {
+                ys[n - 1]
+            } else {
+                // Cubic Hermite on the segment containing x
+                let k = xs.partition_point(|&px| px <= x) - 1;
+                let h = xs[k + 1] - xs[k];
+                let t = (x - xs[k]) / h;
+                let (t2, t3) = (t * t, t * t * t);
+                (2.0 * t3 - 3.0 * t2 + 1.0) * ys[k]
+                    + (t3 - 2.0 * t2 + t) * h * tangents[k]
+                    + (-2.0 * t3 + 3.0 * t2) * ys[k + 1]
+                    + (t3 - t2) * h * tangents[k + 1]
+            };
+            rounding.to_u8(value)
+        })
+        .collect()
+}
+
+/// Band threshold, OpenCV's `inRange` for one channel: 255 where
+/// `low <= v <= high`, 0 elsewhere. Isolates an intensity band such as
+/// highlighter marks on a grayscale scan; `low > high` selects nothing.
+#[wasm_bindgen]
+pub fn in_range(input: &[u8], width: usize, height: usize, low: u8, high: u8) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    input.iter().map(|&v| if (low..=high).contains(&v) { 255 } else { 0 }).collect()
+}
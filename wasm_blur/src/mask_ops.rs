@@ -0,0 +1,307 @@
+use wasm_bindgen::prelude::*;
+use crate::dilation::dilate;
+use crate::morphology::erode;
+
+/// Flood fill from every border pixel whose "is foreground" state equals
+/// `foreground`, spreading through pixels of the same state.
+/// Returns a per-pixel flag marking the reached pixels.
+pub(crate) fn border_connected(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    foreground: bool,
+    eight_connected: bool,
+) -> Vec<bool> {
+    if width == 0 || height == 0 {
+        return vec![false; width * height];
+    }
+    let border = (0..width)
+        .flat_map(|x| [x, (height - 1) * width + x])
+        .chain((0..height).flat_map(|y| [y * width, y * width + width - 1]));
+    flood_from(input, width, height, foreground, eight_connected, border)
+}
+
+/// Flood fill from the `seeds` pixel indices through pixels whose "is
+/// foreground" state equals `foreground`; seeds in the other state are
+/// ignored. Each pixel is visited once, so the cost is linear in the image
+/// size. Returns a per-pixel flag marking the reached pixels.
+pub(crate) fn flood_from(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    foreground: bool,
+    eight_connected: bool,
+    seeds: impl Iterator<Item = usize>,
+) -> Vec<bool> {
+    let pixel_count = width * height;
+    let mut reached = vec![false; pixel_count];
+    if pixel_count == 0 {
+        return reached;
+    }
+    let matches = |idx: usize| (input[idx] != 0) == foreground;
+
+    let mut stack = Vec::with_capacity(2 * (width + height));
+    for idx in seeds {
+        if !reached[idx] && matches(idx) {
+            reached[idx] = true;
+            stack.push(idx);
+        }
+    }
+
+    while let Some(idx) = stack.pop() {
+        let x = (idx % width) as isize;
+        let y = (idx / width) as isize;
+        for dy in -1isize..=1 {
+            for dx in -1isize..=1 {
+                if (dx == 0 && dy == 0) || (!eight_connected && dx != 0 && dy != 0) {
+                    continue;
+                }
+                let nx = x + dx;
+                let ny = y + dy;
+                if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                    continue;
+                }
+                let nidx = ny as usize * width + nx as usize;
+                if !reached[nidx] && matches(nidx) {
+                    reached[nidx] = true;
+                    stack.push(nidx);
+                }
+            }
+        }
+    }
+
+    reached
+}
+
+/// Fills interior holes of a binary mask (foreground = non-zero).
+/// Background regions that are not 4-connected to the image border are set
+/// to 255; everything else is copied unchanged. Using 4-connectivity for the
+/// background pairs with 8-connected objects, so a hole enclosed by a
+/// diagonal-only outline still counts as enclosed.
+#[wasm_bindgen]
+pub fn fill_holes(input: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let outside = border_connected(input, width, height, false, false);
+    let mut output = input.to_vec();
+    for (out, &reached) in output.iter_mut().zip(outside.iter()) {
+        if *out == 0 && !reached {
+            *out = 255;
+        }
+    }
+
+    output
+}
+
+/// Inner outline of the regions in a binary mask: the mask minus its 3x3
+/// erosion, so only foreground pixels touching the background (8-connected)
+/// remain. Handy for drawing detected regions over the original image.
+#[wasm_bindgen]
+pub fn mask_boundary(input: &[u8], width: usize, height: usize) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let eroded = erode(input, width, height, 3);
+    input
+        .iter()
+        .zip(eroded.iter())
+        .map(|(&v, &e)| v.saturating_sub(e))
+        .collect()
+}
+
+/// Binary morphological reconstruction (geodesic dilation of `marker` under
+/// `mask` until stable). Keeps every 8-connected foreground region of `mask`
+/// that contains at least one foreground pixel of `marker`; marker pixels
+/// outside the mask are ignored. Runs as a single flood fill from the marker
+/// pixels instead of iterating dilations.
+///
+/// # Returns
+/// Binary mask, 255 for reconstructed pixels and 0 elsewhere
+#[wasm_bindgen]
+pub fn reconstruct(marker: &[u8], mask: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let pixel_count = width * height;
+    if marker.len() != pixel_count || mask.len() != pixel_count {
+        panic!("Input array sizes don't match width * height");
+    }
+
+    let seeds = (0..pixel_count).filter(|&i| marker[i] != 0);
+    flood_from(mask, width, height, true, true, seeds)
+        .into_iter()
+        .map(|reached| if reached { 255 } else { 0 })
+        .collect()
+}
+
+/// Removes foreground regions (non-zero, 8-connected) that touch any image
+/// edge, e.g. objects cut off by the frame after thresholding a page. This
+/// is reconstruction from the border pixels: the touching regions are set to
+/// 0 and everything else is copied unchanged.
+#[wasm_bindgen]
+pub fn clear_border(input: &[u8], width: usize, height: usize) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let touching = border_connected(input, width, height, true, true);
+    input
+        .iter()
+        .zip(touching.iter())
+        .map(|(&v, &reached)| if reached { 0 } else { v })
+        .collect()
+}
+
+/// Opening by reconstruction of a binary mask: erode with a
+/// `kernel_size` x `kernel_size` square, then reconstruct under the
+/// original. Regions too small to survive the erosion disappear, while every
+/// surviving region comes back with its exact original outline instead of
+/// the rounded shape of a plain opening.
+///
+/// # Returns
+/// Binary mask, 255 for kept pixels and 0 elsewhere
+#[wasm_bindgen]
+pub fn opening_by_reconstruction(input: &[u8], width: usize, height: usize, kernel_size: usize) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let eroded = erode(input, width, height, kernel_size);
+    reconstruct(&eroded, input, width, height)
+}
+
+/// Closing by reconstruction of a binary mask, the dual of
+/// `opening_by_reconstruction`: dilate with a `kernel_size` x `kernel_size`
+/// square, then reconstruct the background from what the dilation left
+/// uncovered. Holes and gaps that the dilation fills are closed, and all
+/// other background keeps its original outline.
+///
+/// # Returns
+/// Binary mask, 255 for foreground and 0 elsewhere
+#[wasm_bindgen]
+pub fn closing_by_reconstruction(input: &[u8], width: usize, height: usize, kernel_size: usize) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let dilated = dilate(input, width, height, kernel_size);
+    let seeds = (0..pixel_count).filter(|&i| dilated[i] == 0);
+    flood_from(input, width, height, false, true, seeds)
+        .into_iter()
+        .map(|background| if background { 0 } else { 255 })
+        .collect()
+}
+
+/// Removes foreground regions (non-zero, 8-connected) of fewer than
+/// `min_area` pixels, e.g. specks of noise left by thresholding. Pixels of
+/// the removed regions are set to 0; everything else is copied unchanged.
+#[wasm_bindgen]
+pub fn remove_small_objects(input: &[u8], width: usize, height: usize, min_area: usize) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let mut output = input.to_vec();
+    let mut visited = vec![false; pixel_count];
+    let mut stack = Vec::new();
+    let mut region = Vec::new();
+    for start in 0..pixel_count {
+        if visited[start] || input[start] == 0 {
+            continue;
+        }
+        visited[start] = true;
+        stack.push(start);
+        region.clear();
+        while let Some(idx) = stack.pop() {
+            region.push(idx);
+            let (x, y) = (idx % width, idx / width);
+            for ny in y.saturating_sub(1)..(y + 2).min(height) {
+                for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                    let nidx = ny * width + nx;
+                    if !visited[nidx] && input[nidx] != 0 {
+                        visited[nidx] = true;
+                        stack.push(nidx);
+                    }
+                }
+            }
+        }
+        if region.len() < min_area {
+            for &idx in &region {
+                output[idx] = 0;
+            }
+        }
+    }
+
+    output
+}
+
+/// Run-length encoding of a binary mask (foreground = non-zero), scanned
+/// row-major over the whole image. Runs alternate background, foreground,
+/// background, ... and always start with a background run, which is 0 when
+/// the first pixel is foreground. Runs continue across row ends, and the
+/// counts sum to `width * height`.
+#[wasm_bindgen]
+pub fn rle_encode(input: &[u8], width: usize, height: usize) -> Vec<u32> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let mut runs = Vec::new();
+    let mut foreground = false;
+    let mut length = 0u32;
+    for &v in input {
+        if (v != 0) != foreground {
+            runs.push(length);
+            foreground = !foreground;
+            length = 0;
+        }
+        length += 1;
+    }
+    if length > 0 {
+        runs.push(length);
+    }
+
+    runs
+}
+
+/// Inverse of `rle_encode`: expands alternating background / foreground
+/// runs (background first) into a mask with 0 and 255.
+#[wasm_bindgen]
+pub fn rle_decode(runs: &[u32], width: usize, height: usize) -> Vec<u8> {
+    let pixel_count = width * height;
+    if runs.iter().map(|&r| r as usize).sum::<usize>() != pixel_count {
+        panic!("Run lengths don't sum to width * height");
+    }
+
+    let mut output = Vec::with_capacity(pixel_count);
+    for (i, &run) in runs.iter().enumerate() {
+        let value = if i.is_multiple_of(2) { 0 } else { 255 };
+        output.resize(output.len() + run as usize, value);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rle_round_trip() {
+        // Starts with foreground, and runs cross both row ends
+        let (width, height) = (4, 3);
+        let mask = [255, 255, 0, 0, 0, 0, 0, 9, 9, 9, 9, 9];
+        let runs = rle_encode(&mask, width, height);
+        assert_eq!(runs, vec![0, 2, 5, 5]);
+        let binary: Vec<u8> = mask.iter().map(|&v| if v != 0 { 255 } else { 0 }).collect();
+        assert_eq!(rle_decode(&runs, width, height), binary);
+
+        let background = vec![0u8; width * height];
+        let runs = rle_encode(&background, width, height);
+        assert_eq!(runs, vec![12]);
+        assert_eq!(rle_decode(&runs, width, height), background);
+    }
+}
@@ -0,0 +1,133 @@
+use std::borrow::Cow;
+use wasm_bindgen::prelude::*;
+
+/// Allocates `len` bytes inside WASM memory and returns a pointer to them.
+/// The contents are uninitialized; the buffer must be released with `free`
+/// using the same `len`.
+///
+/// Together with the `*_in_place` functions this avoids the copies
+/// wasm-bindgen makes for `&[u8]` arguments and `Vec<u8>` results: write
+/// pixels through `new Uint8Array(memory.buffer, ptr, len)`, call the
+/// in-place filter, and read the result through a view again. Views are
+/// invalidated whenever WASM memory grows, so recreate them after each call.
+#[wasm_bindgen]
+pub fn alloc(len: usize) -> *mut u8 {
+    let mut buffer = Vec::<u8>::with_capacity(len);
+    let ptr = buffer.as_mut_ptr();
+    std::mem::forget(buffer);
+    ptr
+}
+
+/// Releases a buffer obtained from `alloc`.
+///
+/// # Safety
+/// `ptr` must come from `alloc(len)` with the same `len`, and must not be used
+/// (or freed again) afterwards.
+#[wasm_bindgen]
+pub unsafe fn free(ptr: *mut u8, len: usize) {
+    drop(Vec::from_raw_parts(ptr, 0, len));
+}
+
+/// Borrows `width * height` bytes at `ptr` as a mutable slice.
+///
+/// # Safety
+/// `ptr` must point to at least `width * height` bytes from `alloc` that are
+/// not aliased for the duration of the call.
+unsafe fn image_slice<'a>(ptr: *mut u8, width: usize, height: usize) -> &'a mut [u8] {
+    if ptr.is_null() {
+        panic!("Null image pointer");
+    }
+    std::slice::from_raw_parts_mut(ptr, width * height)
+}
+
+/// `apply_lut` on a buffer in WASM memory, without any intermediate allocation.
+///
+/// # Safety
+/// `ptr` must point to `width * height` bytes obtained from `alloc`.
+#[wasm_bindgen]
+pub unsafe fn apply_lut_in_place(ptr: *mut u8, width: usize, height: usize, lut: &[u8]) {
+    if lut.len() != 256 {
+        panic!("LUT must have exactly 256 entries");
+    }
+    let image = image_slice(ptr, width, height);
+    for v in image.iter_mut() {
+        *v = lut[*v as usize];
+    }
+}
+
+/// `blur` on a buffer in WASM memory; the result overwrites the input. The
+/// blur still allocates one image-sized temporary, copied back at the end.
+///
+/// # Safety
+/// `ptr` must point to `width * height` bytes obtained from `alloc`.
+#[wasm_bindgen]
+pub unsafe fn blur_in_place(ptr: *mut u8, width: usize, height: usize, kernel_size: usize, sigma: f32) {
+    let image = image_slice(ptr, width, height);
+    let result = crate::gaussian_blur::blur(image, width, height, kernel_size, sigma);
+    image.copy_from_slice(&result);
+}
+
+/// `erode` on a buffer in WASM memory; the result overwrites the input.
+/// Uses a single scratch allocation holding a copy of the input and the
+/// pass buffer, and writes the vertical pass straight into the image.
+///
+/// # Safety
+/// `ptr` must point to `width * height` bytes obtained from `alloc`.
+#[wasm_bindgen]
+pub unsafe fn erode_in_place(ptr: *mut u8, width: usize, height: usize, kernel_size: usize) {
+    let image = image_slice(ptr, width, height);
+    let mut scratch = vec![0u8; 2 * image.len()];
+    let (source, temp) = scratch.split_at_mut(image.len());
+    source.copy_from_slice(image);
+    crate::morphology::erode_into(source, width, height, kernel_size, image, temp);
+}
+
+/// `dilate` on a buffer in WASM memory; the result overwrites the input.
+/// Uses a single scratch allocation like `erode_in_place`.
+///
+/// # Safety
+/// `ptr` must point to `width * height` bytes obtained from `alloc`.
+#[wasm_bindgen]
+pub unsafe fn dilate_in_place(ptr: *mut u8, width: usize, height: usize, kernel_size: usize) {
+    let image = image_slice(ptr, width, height);
+    let mut scratch = vec![0u8; 2 * image.len()];
+    let (source, temp) = scratch.split_at_mut(image.len());
+    source.copy_from_slice(image);
+    crate::dilation::dilate_into(source, width, height, kernel_size, image, temp);
+}
+
+/// `unsharp_mask` on a buffer in WASM memory; the result overwrites the
+/// input. The filter still allocates one image-sized temporary, copied back
+/// at the end.
+///
+/// # Safety
+/// `ptr` must point to `width * height` bytes obtained from `alloc`.
+#[wasm_bindgen]
+pub unsafe fn unsharp_mask_in_place(ptr: *mut u8, width: usize, height: usize, amount: f32, radius: usize) {
+    let image = image_slice(ptr, width, height);
+    let result = crate::unsharp_mask::unsharp_mask(image, width, height, amount, radius);
+    image.copy_from_slice(&result);
+}
+
+/// Views an image whose rows start `stride` bytes apart (e.g. aligned rows
+/// from a GPU readback) as a tightly packed `width * height` buffer. Borrows
+/// when the rows are already contiguous and copies row by row otherwise; the
+/// last row only needs `width` bytes.
+pub(crate) fn packed_rows(input: &[u8], width: usize, height: usize, stride: usize) -> Cow<'_, [u8]> {
+    if stride < width {
+        panic!("Row stride must be at least width");
+    }
+    let required = if height == 0 { 0 } else { (height - 1) * stride + width };
+    if input.len() < required {
+        panic!("Input array is too small for stride * height");
+    }
+    if stride == width {
+        return Cow::Borrowed(&input[..width * height]);
+    }
+
+    let mut packed = Vec::with_capacity(width * height);
+    for y in 0..height {
+        packed.extend_from_slice(&input[y * stride..y * stride + width]);
+    }
+    Cow::Owned(packed)
+}
@@ -0,0 +1,167 @@
+use wasm_bindgen::prelude::*;
+
+/// Raw spatial moments up to third order, `m[p][q]` = sum of x^p * y^q.
+type RawMoments = [[f64; 4]; 4];
+
+/// Moments of the non-zero pixels of a binary image, each pixel at its
+/// integer (x, y) coordinate.
+fn image_moments(input: &[u8], width: usize) -> RawMoments {
+    let mut m = [[0.0; 4]; 4];
+    for (idx, _) in input.iter().enumerate().filter(|(_, &v)| v != 0) {
+        let (x, y) = ((idx % width) as f64, (idx / width) as f64);
+        let xs = [1.0, x, x * x, x * x * x];
+        let ys = [1.0, y, y * y, y * y * y];
+        for p in 0..4 {
+            for q in 0..4 - p {
+                m[p][q] += xs[p] * ys[q];
+            }
+        }
+    }
+    m
+}
+
+/// Moments of the area enclosed by a polygon, by Green's theorem over its
+/// edges (as OpenCV's `contourMoments`). Orientation does not matter.
+fn polygon_moments(points: &[(f64, f64)]) -> RawMoments {
+    let mut m = [[0.0; 4]; 4];
+    for (i, &(x0, y0)) in points.iter().enumerate() {
+        let (x1, y1) = points[(i + 1) % points.len()];
+        let a = x0 * y1 - x1 * y0;
+        m[0][0] += a;
+        m[1][0] += a * (x0 + x1);
+        m[0][1] += a * (y0 + y1);
+        m[2][0] += a * (x0 * x0 + x0 * x1 + x1 * x1);
+        m[1][1] += a * (x0 * (2.0 * y0 + y1) + x1 * (y0 + 2.0 * y1));
+        m[0][2] += a * (y0 * y0 + y0 * y1 + y1 * y1);
+        m[3][0] += a * (x0 + x1) * (x0 * x0 + x1 * x1);
+        m[2][1] += a * (x0 * x0 * (3.0 * y0 + y1) + 2.0 * x0 * x1 * (y0 + y1) + x1 * x1 * (y0 + 3.0 * y1));
+        m[1][2] += a * (y0 * y0 * (3.0 * x0 + x1) + 2.0 * y0 * y1 * (x0 + x1) + y1 * y1 * (x0 + 3.0 * x1));
+        m[0][3] += a * (y0 + y1) * (y0 * y0 + y1 * y1);
+    }
+    let scale = [[2.0, 6.0, 12.0, 20.0], [6.0, 24.0, 60.0, 0.0], [12.0, 60.0, 0.0, 0.0], [20.0, 0.0, 0.0, 0.0]];
+    let sign = if m[0][0] < 0.0 { -1.0 } else { 1.0 };
+    for p in 0..4 {
+        for q in 0..4 - p {
+            m[p][q] *= sign / scale[p][q];
+        }
+    }
+    m
+}
+
+/// The seven Hu invariants from raw moments; all zero for an empty region.
+fn hu_from_raw(m: &RawMoments) -> [f64; 7] {
+    let area = m[0][0];
+    if area.abs() < 1e-12 {
+        return [0.0; 7];
+    }
+    let (cx, cy) = (m[1][0] / area, m[0][1] / area);
+
+    // Central moments, then scale-normalized: eta = mu / area^(1 + (p + q) / 2)
+    let mu20 = m[2][0] - cx * m[1][0];
+    let mu11 = m[1][1] - cx * m[0][1];
+    let mu02 = m[0][2] - cy * m[0][1];
+    let mu30 = m[3][0] - 3.0 * cx * m[2][0] + 2.0 * cx * cx * m[1][0];
+    let mu21 = m[2][1] - 2.0 * cx * m[1][1] - cy * m[2][0] + 2.0 * cx * cx * m[0][1];
+    let mu12 = m[1][2] - 2.0 * cy * m[1][1] - cx * m[0][2] + 2.0 * cy * cy * m[1][0];
+    let mu03 = m[0][3] - 3.0 * cy * m[0][2] + 2.0 * cy * cy * m[0][1];
+    let (s2, s3) = (area * area, area.powf(2.5));
+    let (n20, n11, n02) = (mu20 / s2, mu11 / s2, mu02 / s2);
+    let (n30, n21, n12, n03) = (mu30 / s3, mu21 / s3, mu12 / s3, mu03 / s3);
+
+    let (a, b) = (n30 + n12, n21 + n03);
+    let (c, d) = (n30 - 3.0 * n12, 3.0 * n21 - n03);
+    [
+        n20 + n02,
+        (n20 - n02) * (n20 - n02) + 4.0 * n11 * n11,
+        c * c + d * d,
+        a * a + b * b,
+        c * a * (a * a - 3.0 * b * b) + d * b * (3.0 * a * a - b * b),
+        (n20 - n02) * (a * a - b * b) + 4.0 * n11 * a * b,
+        d * a * (a * a - 3.0 * b * b) - c * b * (3.0 * a * a - b * b),
+    ]
+}
+
+/// Seven Hu moments of the foreground (non-zero pixels) of a binary mask:
+/// invariant to translation, scale and rotation, and the seventh changes
+/// sign under reflection. Pass a mask holding one region, e.g. a single
+/// component cut out of a labelling.
+///
+/// # Returns
+/// `[h1, ..., h7]`, all 0 for an empty mask
+#[wasm_bindgen]
+pub fn hu_moments(input: &[u8], width: usize, height: usize) -> Vec<f64> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    hu_from_raw(&image_moments(input, width)).to_vec()
+}
+
+/// `hu_moments` of the polygon outlined by a contour `[x0, y0, x1, y1, ...]`,
+/// computed exactly from its vertices, so simplified contours work as well
+/// as full boundaries. Pixel-accurate masks and contours of the same shape
+/// agree closely but not exactly, since a mask counts whole pixels.
+#[wasm_bindgen]
+pub fn hu_moments_contour(contour: &[f32]) -> Vec<f64> {
+    if !contour.len().is_multiple_of(2) {
+        panic!("Contour array must hold (x, y) pairs");
+    }
+
+    let points: Vec<(f64, f64)> = contour.chunks_exact(2).map(|p| (p[0] as f64, p[1] as f64)).collect();
+    hu_from_raw(&polygon_moments(&points)).to_vec()
+}
+
+/// Dissimilarity of two shapes from their Hu moments, OpenCV's
+/// `CONTOURS_MATCH_I1`: sum over i of |1/m_a - 1/m_b| with
+/// m = sign(h_i) * log10|h_i|. 0 for identical shapes, growing with the
+/// difference; invariants that are (numerically) zero in either shape are
+/// skipped.
+#[wasm_bindgen]
+pub fn match_shapes(a_moments: &[f64], b_moments: &[f64]) -> f64 {
+    if a_moments.len() != 7 || b_moments.len() != 7 {
+        panic!("Hu moments must have 7 elements");
+    }
+
+    let log_scale = |h: f64| h.signum() * h.abs().log10();
+    a_moments
+        .iter()
+        .zip(b_moments)
+        .filter(|(&a, &b)| a.abs() > 1e-5 && b.abs() > 1e-5)
+        .map(|(&a, &b)| (1.0 / log_scale(a) - 1.0 / log_scale(b)).abs())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hu_moments_rotation_invariant() {
+        // An L shape and the same shape rotated by 90 degrees
+        let (width, height) = (12, 12);
+        let mut mask = vec![0u8; width * height];
+        let mut rotated = vec![0u8; width * height];
+        for y in 2..10 {
+            for x in 2..6 {
+                mask[y * width + x] = 255;
+                rotated[x * width + (width - 1 - y)] = 255;
+            }
+        }
+        for y in 7..10 {
+            for x in 6..9 {
+                mask[y * width + x] = 255;
+                rotated[x * width + (width - 1 - y)] = 255;
+            }
+        }
+
+        let a = hu_moments(&mask, width, height);
+        let b = hu_moments(&rotated, width, height);
+        for (ha, hb) in a.iter().zip(&b) {
+            assert!((ha - hb).abs() <= 1e-9 * ha.abs().max(1e-12));
+        }
+        assert!(match_shapes(&a, &b) < 1e-9);
+
+        let square = hu_moments_contour(&[0.0, 0.0, 10.0, 0.0, 10.0, 10.0, 0.0, 10.0]);
+        assert!((square[0] - 1.0 / 6.0).abs() < 1e-9);
+    }
+}
@@ -1,8 +1,26 @@
 use wasm_bindgen::prelude::*;
-use crate::dilation::dilate;
+use crate::dilation::{dilate, dilate_into};
+use crate::memory::packed_rows;
+use crate::rounding::RoundMode;
+use crate::border::{pad_image, BorderMode};
+use crate::roi::crop;
+use crate::gaussian_blur::gaussian_iir;
+
+#[cfg(all(target_arch = "wasm32", feature = "simd"))]
+use std::arch::wasm32::*;
 
 /// Erode operation - inverse of dilate (uses min instead of max)
 /// Uses separable (two-pass) approach for square structuring elements.
+/// The min of the row minima over a clamped square window is exactly the min
+/// over the window itself, so the result is bit-identical to a direct 2D
+/// erosion with a flat `kernel_size` x `kernel_size` element and replicated
+/// borders (OpenCV's `erode` with `BORDER_REPLICATE`).
+///
+/// The window spans offsets `-(kernel_size / 2)` to `kernel_size - 1 -
+/// kernel_size / 2` on each axis. For odd sizes that is centered; for even
+/// sizes it reaches one pixel further up and left than down and right (a 2x2
+/// kernel covers `x - 1..=x`, `y - 1..=y`), so dark features grow one pixel
+/// more towards the bottom-right. `dilate` places its window the same way.
 #[wasm_bindgen]
 pub fn erode(
     input: &[u8],
@@ -10,9 +28,74 @@ pub fn erode(
     height: usize,
     kernel_size: usize,
 ) -> Vec<u8> {
-    let half_kernel = kernel_size / 2;
     let mut temp = vec![255u8; width * height];
     let mut eroded = vec![255u8; width * height];
+    erode_into(input, width, height, kernel_size, &mut eroded, &mut temp);
+    eroded
+}
+
+/// `erode` with a selectable border mode instead of replicated edges, e.g.
+/// `BorderMode.Wrap` so a tileable texture stays seamless.
+#[wasm_bindgen]
+pub fn erode_with_border(input: &[u8], width: usize, height: usize, kernel_size: usize, border: BorderMode) -> Vec<u8> {
+    with_border(input, width, height, kernel_size, border, erode)
+}
+
+/// `dilate` with a selectable border mode instead of replicated edges.
+#[wasm_bindgen]
+pub fn dilate_with_border(input: &[u8], width: usize, height: usize, kernel_size: usize, border: BorderMode) -> Vec<u8> {
+    with_border(input, width, height, kernel_size, border, dilate)
+}
+
+/// Runs a square-window filter on a copy padded by `kernel_size / 2` (its
+/// largest reach) according to `border`, and crops the result back.
+fn with_border(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    kernel_size: usize,
+    border: BorderMode,
+    filter: fn(&[u8], usize, usize, usize) -> Vec<u8>,
+) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+
+    let pad = kernel_size / 2;
+    let padded = pad_image(input, width, height, pad, border);
+    let padded_width = width + 2 * pad;
+    let filtered = filter(&padded, padded_width, height + 2 * pad, kernel_size);
+    crop(&filtered, padded_width, pad, pad, width, height)
+}
+
+/// `erode` for an image whose rows start `stride` bytes apart. The result is
+/// tightly packed (`width * height` bytes).
+#[wasm_bindgen]
+pub fn erode_strided(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+    kernel_size: usize,
+) -> Vec<u8> {
+    let packed = packed_rows(input, width, height, stride);
+    erode(&packed, width, height, kernel_size)
+}
+
+/// `erode` writing into caller-provided buffers.
+/// `eroded` and `temp` must both hold `width * height` bytes.
+pub(crate) fn erode_into(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    kernel_size: usize,
+    eroded: &mut [u8],
+    temp: &mut [u8],
+) {
+    let half_kernel = kernel_size / 2;
 
     // Horizontal pass (min filter)
     for y in 0..height {
@@ -31,27 +114,57 @@ pub fn erode(
         }
     }
 
-    // Vertical pass (min filter)
+    // Vertical pass (min filter), folding whole source rows into the output
+    // row so every access walks memory contiguously
     for y in 0..height {
-        for x in 0..width {
-            let mut min_val = 255u8;
-            for k in 0..kernel_size {
-                let dy = k as isize - half_kernel as isize;
-                let ny = (y as isize + dy).clamp(0, (height - 1) as isize) as usize;
-                let val = temp[ny * width + x];
-                if val < min_val {
-                    min_val = val;
-                }
-            }
-            eroded[y * width + x] = min_val;
+        let out_row = &mut eroded[y * width..(y + 1) * width];
+        out_row.fill(255);
+        for k in 0..kernel_size {
+            let dy = k as isize - half_kernel as isize;
+            let ny = (y as isize + dy).clamp(0, (height - 1) as isize) as usize;
+            min_row_into(out_row, &temp[ny * width..(ny + 1) * width]);
         }
     }
+}
 
-    eroded
+#[cfg(all(target_arch = "wasm32", feature = "simd"))]
+#[target_feature(enable = "simd128")]
+unsafe fn min_row_simd(out: &mut [u8], row: &[u8]) {
+    let chunks = out.len() / 16;
+    for chunk_idx in 0..chunks {
+        let x = chunk_idx * 16;
+        let a = v128_load(out.as_ptr().add(x) as *const v128);
+        let b = v128_load(row.as_ptr().add(x) as *const v128);
+        v128_store(out.as_mut_ptr().add(x) as *mut v128, u8x16_min(a, b));
+    }
+    for x in (chunks * 16)..out.len() {
+        if row[x] < out[x] {
+            out[x] = row[x];
+        }
+    }
+}
+
+/// `out[x] = min(out[x], row[x])`, 16 pixels at a time with the `simd` feature.
+fn min_row_into(out: &mut [u8], row: &[u8]) {
+    #[cfg(all(target_arch = "wasm32", feature = "simd"))]
+    unsafe {
+        min_row_simd(out, row);
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", feature = "simd")))]
+    for (out, &val) in out.iter_mut().zip(row) {
+        if val < *out {
+            *out = val;
+        }
+    }
 }
 
 /// Morphological close operation: dilate then erode.
-/// Closes small gaps in binary edges.
+/// Closes small gaps in binary edges. Use an odd `kernel_size`: with even
+/// sizes both passes lean the same way (see `erode`), so each iteration
+/// shifts the result one pixel towards the bottom-right.
+/// All iterations share one result buffer, one ping-pong buffer and one
+/// scratch buffer for the separable passes.
 #[wasm_bindgen]
 pub fn morphological_close(
     input: &[u8],
@@ -61,13 +174,309 @@ pub fn morphological_close(
     iterations: usize,
 ) -> Vec<u8> {
     let mut current = input.to_vec();
+    if iterations == 0 {
+        return current;
+    }
+    let mut other = vec![0u8; width * height];
+    let mut temp = vec![0u8; width * height];
 
     for _ in 0..iterations {
         // Dilate first (close gaps)
-        current = dilate(&current, width, height, kernel_size);
+        dilate_into(&current, width, height, kernel_size, &mut other, &mut temp);
         // Then erode (restore size)
-        current = erode(&current, width, height, kernel_size);
+        erode_into(&other, width, height, kernel_size, &mut current, &mut temp);
+    }
+
+    current
+}
+
+/// Morphological open operation: erode then dilate.
+/// Removes specks smaller than the kernel while keeping larger shapes. As
+/// with `morphological_close`, even `kernel_size`s shift the result one pixel
+/// towards the bottom-right per iteration.
+/// Uses the same three reusable buffers as `morphological_close`.
+#[wasm_bindgen]
+pub fn morphological_open(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    kernel_size: usize,
+    iterations: usize,
+) -> Vec<u8> {
+    let mut current = input.to_vec();
+    if iterations == 0 {
+        return current;
+    }
+    let mut other = vec![0u8; width * height];
+    let mut temp = vec![0u8; width * height];
+
+    for _ in 0..iterations {
+        erode_into(&current, width, height, kernel_size, &mut other, &mut temp);
+        dilate_into(&other, width, height, kernel_size, &mut current, &mut temp);
     }
 
     current
-}
\ No newline at end of file
+}
+
+/// Mask cleanup in one call: `morphological_open` then
+/// `morphological_close` (or the other way round with `close_first`), each
+/// with `iterations` iterations, so specks and small holes both disappear.
+/// Opening first drops specks before the closing could merge them into
+/// nearby shapes; closing first keeps thin broken strokes joined instead.
+/// All passes share the same three buffers.
+#[wasm_bindgen]
+pub fn morphological_smooth(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    kernel_size: usize,
+    iterations: usize,
+    close_first: bool,
+) -> Vec<u8> {
+    let mut current = input.to_vec();
+    if iterations == 0 {
+        return current;
+    }
+    let mut other = vec![0u8; width * height];
+    let mut temp = vec![0u8; width * height];
+
+    for closing in [close_first, !close_first] {
+        for _ in 0..iterations {
+            if closing {
+                dilate_into(&current, width, height, kernel_size, &mut other, &mut temp);
+                erode_into(&other, width, height, kernel_size, &mut current, &mut temp);
+            } else {
+                erode_into(&current, width, height, kernel_size, &mut other, &mut temp);
+                dilate_into(&other, width, height, kernel_size, &mut current, &mut temp);
+            }
+        }
+    }
+
+    current
+}
+
+/// Evens out uneven lighting on a page with dark content on a light
+/// background. The background is estimated by a grayscale closing with a
+/// `kernel_size` x `kernel_size` square, which fills in every dark detail
+/// narrower than the kernel (pick it wider than the thickest stroke), and
+/// each pixel is divided by it: `v * 255 / background`. Paper comes out
+/// near 255 everywhere while ink keeps its contrast to the paper around it.
+#[wasm_bindgen]
+pub fn flatten_background(input: &[u8], width: usize, height: usize, kernel_size: usize) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let background = morphological_close(input, width, height, kernel_size, 1);
+    let rounding = RoundMode::current();
+    input
+        .iter()
+        .zip(background.iter())
+        .map(|(&v, &bg)| if bg == 0 { 255 } else { rounding.to_u8(v as f32 * 255.0 / bg as f32) })
+        .collect()
+}
+
+/// Removes a smooth shading gradient (e.g. a diagonal shadow on a phone
+/// capture) from a page with dark content on a light background, by
+/// subtraction rather than the division of `flatten_background`.
+///
+/// The illumination field is a grayscale closing with a square of side
+/// `2 * radius + 1`, which erases every dark detail narrower than that,
+/// smoothed with a Gaussian of sigma `radius / 2` so it carries no blocky
+/// edges. Each pixel's darkness relative to the field, `field - v`, is then
+/// rescaled so the darkest pixel becomes 0 while the background becomes 255.
+/// Unlike a percentile stretch this keeps sparse ink from turning paper
+/// noise into visible texture.
+///
+/// # Arguments
+/// * `radius` - Scale of what counts as background: larger than the widest
+///   dark stroke or object to keep, smaller than the shadow's extent
+#[wasm_bindgen]
+pub fn subtract_illumination(input: &[u8], width: usize, height: usize, radius: usize) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let closed = morphological_close(input, width, height, 2 * radius + 1, 1);
+    let mut field: Vec<f32> = closed.iter().map(|&v| v as f32).collect();
+    gaussian_iir(&mut field, width, height, radius as f32 / 2.0);
+    let darkness: Vec<f32> = input.iter().zip(&field).map(|(&v, &bg)| (bg - v as f32).max(0.0)).collect();
+    let darkest = darkness.iter().copied().fold(0.0f32, f32::max);
+    if darkest <= 0.0 {
+        return vec![255; input.len()];
+    }
+
+    let rounding = RoundMode::current();
+    let scale = 255.0 / darkest;
+    darkness.iter().map(|&d| rounding.to_u8(255.0 - d * scale)).collect()
+}
+
+/// Min (erode) or max (dilate) over an arbitrary list of (dx, dy) offsets,
+/// with clamped borders like the square erode/dilate.
+fn morph_offsets(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    offsets: &[(isize, isize)],
+    dilate: bool,
+) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let mut output = vec![0u8; pixel_count];
+    for y in 0..height {
+        for x in 0..width {
+            let mut acc = if dilate { 0u8 } else { 255u8 };
+            for &(dx, dy) in offsets {
+                let nx = (x as isize + dx).clamp(0, (width - 1) as isize) as usize;
+                let ny = (y as isize + dy).clamp(0, (height - 1) as isize) as usize;
+                let val = input[ny * width + nx];
+                acc = if dilate { acc.max(val) } else { acc.min(val) };
+            }
+            output[y * width + x] = acc;
+        }
+    }
+
+    output
+}
+
+/// Offsets of the non-zero entries of a structuring element, anchored at its center.
+fn mask_offsets(mask: &[u8], mask_width: usize, mask_height: usize) -> Vec<(isize, isize)> {
+    if mask.len() != mask_width * mask_height {
+        panic!("Mask size doesn't match mask_width * mask_height");
+    }
+    if mask_width.is_multiple_of(2) || mask_height.is_multiple_of(2) {
+        panic!("Mask dimensions must be odd");
+    }
+
+    let half_w = (mask_width / 2) as isize;
+    let half_h = (mask_height / 2) as isize;
+    let mut offsets = Vec::new();
+    for my in 0..mask_height {
+        for mx in 0..mask_width {
+            if mask[my * mask_width + mx] != 0 {
+                offsets.push((mx as isize - half_w, my as isize - half_h));
+            }
+        }
+    }
+    offsets
+}
+
+/// Offsets inside a disk of the given radius (Euclidean distance <= radius).
+fn disk_offsets(radius: usize) -> Vec<(isize, isize)> {
+    let r = radius as isize;
+    let r_sq = r * r;
+    let mut offsets = Vec::new();
+    for dy in -r..=r {
+        for dx in -r..=r {
+            if dx * dx + dy * dy <= r_sq {
+                offsets.push((dx, dy));
+            }
+        }
+    }
+    offsets
+}
+
+/// Erode with an arbitrary structuring element given as a `mask_width` x
+/// `mask_height` mask (non-zero = part of the element), anchored at its center.
+#[wasm_bindgen]
+pub fn erode_with_mask(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    mask: &[u8],
+    mask_width: usize,
+    mask_height: usize,
+) -> Vec<u8> {
+    let offsets = mask_offsets(mask, mask_width, mask_height);
+    morph_offsets(input, width, height, &offsets, false)
+}
+
+/// Dilate with an arbitrary structuring element given as a `mask_width` x
+/// `mask_height` mask (non-zero = part of the element), anchored at its center.
+#[wasm_bindgen]
+pub fn dilate_with_mask(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    mask: &[u8],
+    mask_width: usize,
+    mask_height: usize,
+) -> Vec<u8> {
+    let offsets = mask_offsets(mask, mask_width, mask_height);
+    morph_offsets(input, width, height, &offsets, true)
+}
+
+/// Reference dilation: a direct 2D max over the `kernel_size` x
+/// `kernel_size` window of every pixel, with replicated borders and the same
+/// window placement as `dilate` (offsets `-(kernel_size / 2)` up to
+/// `kernel_size - 1 - kernel_size / 2`). Much slower than the separable
+/// `dilate`, which must match it exactly; meant for validating it.
+#[wasm_bindgen]
+pub fn dilate_2d(input: &[u8], width: usize, height: usize, kernel_size: usize) -> Vec<u8> {
+    let half = (kernel_size / 2) as isize;
+    let offsets: Vec<(isize, isize)> = (0..kernel_size as isize)
+        .flat_map(|dy| (0..kernel_size as isize).map(move |dx| (dx - half, dy - half)))
+        .collect();
+    morph_offsets(input, width, height, &offsets, true)
+}
+
+/// Erode with a circular structuring element of the given radius.
+/// Gives rounded mask boundaries instead of the blocky result of a square kernel.
+#[wasm_bindgen]
+pub fn disk_erode(input: &[u8], width: usize, height: usize, radius: usize) -> Vec<u8> {
+    morph_offsets(input, width, height, &disk_offsets(radius), false)
+}
+
+/// Dilate with a circular structuring element of the given radius.
+#[wasm_bindgen]
+pub fn disk_dilate(input: &[u8], width: usize, height: usize, radius: usize) -> Vec<u8> {
+    morph_offsets(input, width, height, &disk_offsets(radius), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dilation::dilate;
+
+    #[test]
+    fn test_separable_matches_direct_2d() {
+        let (width, height) = (23, 17);
+        let mut state = 12345u32;
+        let input: Vec<u8> = (0..width * height)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                (state >> 16) as u8
+            })
+            .collect();
+
+        for kernel_size in [1, 3, 5, 7] {
+            let square = vec![1u8; kernel_size * kernel_size];
+            assert_eq!(
+                erode(&input, width, height, kernel_size),
+                erode_with_mask(&input, width, height, &square, kernel_size, kernel_size)
+            );
+            assert_eq!(
+                dilate(&input, width, height, kernel_size),
+                dilate_with_mask(&input, width, height, &square, kernel_size, kernel_size)
+            );
+        }
+        // Even sizes included: both place the window the same way
+        for kernel_size in 1..=8 {
+            assert_eq!(dilate(&input, width, height, kernel_size), dilate_2d(&input, width, height, kernel_size));
+        }
+    }
+
+    #[test]
+    fn test_even_kernel_anchor() {
+        // A 2x2 window covers (x - 1..=x, y - 1..=y), so a dark dot spreads
+        // one pixel right and down
+        let mut dot = vec![255u8; 25];
+        dot[2 * 5 + 2] = 0;
+        let eroded = erode(&dot, 5, 5, 2);
+        let dark: Vec<usize> = (0..25).filter(|&i| eroded[i] == 0).collect();
+        assert_eq!(dark, vec![12, 13, 17, 18]);
+    }
+}
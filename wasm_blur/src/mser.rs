@@ -0,0 +1,270 @@
+use wasm_bindgen::prelude::*;
+
+/// Regions found by `mser`.
+#[wasm_bindgen]
+pub struct MserRegions {
+    pub(crate) regions: Vec<Vec<u32>>,
+    pub(crate) boxes: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl MserRegions {
+    #[wasm_bindgen(getter)]
+    pub fn num_regions(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// Pixel indices (`y * width + x`) of region `index`.
+    pub fn region(&self, index: usize) -> Vec<u32> {
+        self.regions[index].clone()
+    }
+
+    /// Bounding boxes of all regions as flat `[x, y, width, height, ...]`.
+    #[wasm_bindgen(getter)]
+    pub fn bounding_boxes(&self) -> Vec<u32> {
+        self.boxes.clone()
+    }
+}
+
+const NO_PARENT: usize = usize::MAX;
+
+/// Node of the component tree: a connected set of pixels with intensity
+/// <= `level`, created when it first appears or changes.
+struct Node {
+    level: u8,
+    area: u32,
+    parent: usize,
+}
+
+fn find(uf: &mut [usize], mut p: usize) -> usize {
+    while uf[p] != p {
+        uf[p] = uf[uf[p]];
+        p = uf[p];
+    }
+    p
+}
+
+/// Dark-on-light MSERs (components of the lower level sets) of `input`.
+fn dark_regions(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    delta: u8,
+    min_area: u32,
+    max_area: u32,
+    max_variation: f32,
+) -> Vec<Vec<u32>> {
+    let pixel_count = width * height;
+
+    // Pixels in increasing intensity order (counting sort)
+    let mut starts = [0usize; 257];
+    for &v in input {
+        starts[v as usize + 1] += 1;
+    }
+    for i in 0..256 {
+        starts[i + 1] += starts[i];
+    }
+    let mut order = vec![0usize; pixel_count];
+    let mut next = starts;
+    for (i, &v) in input.iter().enumerate() {
+        order[next[v as usize]] = i;
+        next[v as usize] += 1;
+    }
+
+    // Build the component tree with union-find; every pixel starts as its
+    // own node, and nodes merged at the same level become aliases
+    let mut uf: Vec<usize> = (0..pixel_count).collect();
+    let mut added = vec![false; pixel_count];
+    let mut root_node = vec![0usize; pixel_count];
+    let mut pixel_node = vec![0usize; pixel_count];
+    let mut nodes: Vec<Node> = Vec::with_capacity(pixel_count);
+
+    for &p in &order {
+        let level = input[p];
+        let node = nodes.len();
+        nodes.push(Node { level, area: 1, parent: NO_PARENT });
+        pixel_node[p] = node;
+        root_node[p] = node;
+        added[p] = true;
+
+        let (x, y) = (p % width, p / width);
+        let neighbours = [
+            (x > 0).then(|| p - 1),
+            (x + 1 < width).then(|| p + 1),
+            (y > 0).then(|| p - width),
+            (y + 1 < height).then(|| p + width),
+        ];
+        for q in neighbours.into_iter().flatten() {
+            if !added[q] {
+                continue;
+            }
+            let rp = find(&mut uf, p);
+            let rq = find(&mut uf, q);
+            if rp == rq {
+                continue;
+            }
+            // The component of p always has a node at the current level
+            let np = root_node[rp];
+            let nq = root_node[rq];
+            nodes[nq].parent = np;
+            nodes[np].area += nodes[nq].area;
+            uf[rq] = rp;
+        }
+    }
+
+    // Collapse same-level aliases onto their canonical node
+    let mut canonical: Vec<usize> = (0..nodes.len()).collect();
+    for n in (0..nodes.len()).rev() {
+        let parent = nodes[n].parent;
+        if parent != NO_PARENT && nodes[parent].level == nodes[n].level {
+            canonical[n] = canonical[parent];
+        }
+    }
+    let canonical_parent = |n: usize| -> usize {
+        let parent = nodes[n].parent;
+        if parent == NO_PARENT { NO_PARENT } else { canonical[parent] }
+    };
+
+    // Stability: relative growth of the region over `delta` grey levels
+    let mut variation = vec![f32::INFINITY; nodes.len()];
+    for n in 0..nodes.len() {
+        if canonical[n] != n {
+            continue;
+        }
+        let limit = nodes[n].level as u32 + delta as u32;
+        let mut grown = n;
+        loop {
+            let parent = canonical_parent(grown);
+            if parent == NO_PARENT || nodes[parent].level as u32 > limit {
+                break;
+            }
+            grown = parent;
+        }
+        variation[n] = (nodes[grown].area - nodes[n].area) as f32 / nodes[n].area as f32;
+    }
+
+    // Maximally stable: no more variation than the parent or any child; ties
+    // keep both, so flat nested regions are all reported
+    let mut stable = vec![true; nodes.len()];
+    for n in 0..nodes.len() {
+        if canonical[n] != n {
+            continue;
+        }
+        let parent = canonical_parent(n);
+        if parent != NO_PARENT {
+            if variation[n] > variation[parent] {
+                stable[n] = false;
+            }
+            if variation[parent] > variation[n] {
+                stable[parent] = false;
+            }
+        }
+    }
+
+    let selected: Vec<usize> = (0..nodes.len())
+        .filter(|&n| {
+            canonical[n] == n
+                && stable[n]
+                && variation[n] <= max_variation
+                && (min_area..=max_area).contains(&nodes[n].area)
+        })
+        .collect();
+    if selected.is_empty() {
+        return Vec::new();
+    }
+
+    // Gather pixels: walk each pixel up its canonical ancestors and add it to
+    // every selected region on the way
+    let mut slot = vec![usize::MAX; nodes.len()];
+    for (i, &n) in selected.iter().enumerate() {
+        slot[n] = i;
+    }
+    let mut regions = vec![Vec::new(); selected.len()];
+    for p in 0..pixel_count {
+        let mut n = canonical[pixel_node[p]];
+        while n != NO_PARENT {
+            if slot[n] != usize::MAX {
+                regions[slot[n]].push(p as u32);
+            }
+            n = canonical_parent(n);
+        }
+    }
+    regions
+}
+
+/// Maximally Stable Extremal Regions (Matas et al.), built on a union-find
+/// component tree of the image's level sets.
+/// A region is an extremal connected component; its variation is
+/// `(area(level + delta) - area) / area`, and it is kept when that variation
+/// is a local minimum along the tree, at most `max_variation`, and its area is
+/// within `min_area..=max_area`. Both dark regions on a light background and
+/// light regions on a dark background are detected (dark ones first).
+///
+/// # Arguments
+/// * `delta` - Intensity step over which stability is measured (5 is typical)
+/// * `max_variation` - Largest accepted relative area growth (e.g. 0.25)
+///
+/// # Returns
+/// `MserRegions` with per-region pixel lists and bounding boxes
+#[wasm_bindgen]
+pub fn mser(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    delta: u8,
+    min_area: u32,
+    max_area: u32,
+    max_variation: f32,
+) -> MserRegions {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let inverted: Vec<u8> = input.iter().map(|&v| 255 - v).collect();
+    let mut regions = dark_regions(input, width, height, delta, min_area, max_area, max_variation);
+    regions.extend(dark_regions(&inverted, width, height, delta, min_area, max_area, max_variation));
+
+    let mut boxes = Vec::with_capacity(regions.len() * 4);
+    for region in &regions {
+        let (mut x0, mut y0, mut x1, mut y1) = (u32::MAX, u32::MAX, 0, 0);
+        for &p in region {
+            let (x, y) = (p % width as u32, p / width as u32);
+            x0 = x0.min(x);
+            y0 = y0.min(y);
+            x1 = x1.max(x);
+            y1 = y1.max(y);
+        }
+        boxes.extend_from_slice(&[x0, y0, x1 - x0 + 1, y1 - y0 + 1]);
+    }
+
+    MserRegions { regions, boxes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mser_nested_squares() {
+        // Dark 8x8 square inside a mid-grey 24x24 square on white
+        let (width, height) = (40, 40);
+        let mut input = vec![255u8; width * height];
+        for y in 8..32 {
+            for x in 8..32 {
+                input[y * width + x] = if (16..24).contains(&x) && (16..24).contains(&y) { 20 } else { 128 };
+            }
+        }
+
+        let result = mser(&input, width, height, 5, 10, 1000, 0.25);
+        // The white background is too large, and the inverted image has no
+        // region inside the area range
+        assert_eq!(result.num_regions(), 2);
+        assert_eq!(result.bounding_boxes(), vec![16, 16, 8, 8, 8, 8, 24, 24]);
+
+        let dark: Vec<u32> = (16..24).flat_map(|y| (16..24).map(move |x| (y * width + x) as u32)).collect();
+        assert_eq!(result.region(0), dark);
+        let grey = result.region(1);
+        assert_eq!(grey.len(), 24 * 24);
+        assert!(grey.iter().all(|&p| input[p as usize] < 255));
+    }
+}
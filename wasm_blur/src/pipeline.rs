@@ -0,0 +1,244 @@
+use wasm_bindgen::prelude::*;
+use crate::clahe::{clahe_inplace_with, column_weights};
+use crate::dilation::dilate_into;
+use crate::gaussian_blur::blur;
+use crate::histogram::{histogram, otsu_from_histogram};
+use crate::morphology::erode_into;
+use crate::tiling::TileGrid;
+use crate::unsharp_mask::unsharp_mask;
+
+/// One operation of a `Pipeline`, with the parameters it was added with.
+#[derive(Clone, Debug)]
+enum Step {
+    Blur { kernel_size: usize, sigma: f32 },
+    Clahe { tile_grid_x: usize, tile_grid_y: usize, clip_limit: f32 },
+    Unsharp { amount: f32, radius: usize },
+    Threshold { level: u8, invert: bool },
+    ThresholdOtsu { invert: bool },
+    Erode { kernel_size: usize },
+    Dilate { kernel_size: usize },
+    Lut { table: Box<[u8; 256]> },
+}
+
+impl Step {
+    /// JSON object for `Pipeline::describe`.
+    fn describe(&self) -> String {
+        match self {
+            Step::Blur { kernel_size, sigma } => {
+                format!(r#"{{"op":"blur","kernel_size":{},"sigma":{}}}"#, kernel_size, sigma)
+            }
+            Step::Clahe { tile_grid_x, tile_grid_y, clip_limit } => format!(
+                r#"{{"op":"clahe","tile_grid_x":{},"tile_grid_y":{},"clip_limit":{}}}"#,
+                tile_grid_x, tile_grid_y, clip_limit
+            ),
+            Step::Unsharp { amount, radius } => {
+                format!(r#"{{"op":"unsharp","amount":{},"radius":{}}}"#, amount, radius)
+            }
+            Step::Threshold { level, invert } => {
+                format!(r#"{{"op":"threshold","level":{},"invert":{}}}"#, level, invert)
+            }
+            Step::ThresholdOtsu { invert } => format!(r#"{{"op":"threshold_otsu","invert":{}}}"#, invert),
+            Step::Erode { kernel_size } => format!(r#"{{"op":"erode","kernel_size":{}}}"#, kernel_size),
+            Step::Dilate { kernel_size } => format!(r#"{{"op":"dilate","kernel_size":{}}}"#, kernel_size),
+            Step::Lut { table } => {
+                let entries: Vec<String> = table.iter().map(|v| v.to_string()).collect();
+                format!(r#"{{"op":"lut","table":[{}]}}"#, entries.join(","))
+            }
+        }
+    }
+}
+
+/// Panics on NaN or infinite parameters, which `describe` could not write
+/// as JSON.
+fn check_finite(values: &[f32]) {
+    if values.iter().any(|v| !v.is_finite()) {
+        panic!("Pipeline parameters must be finite");
+    }
+}
+
+/// A reproducible sequence of grayscale operations. Steps are appended with
+/// the builder methods, each of which returns the pipeline, and `run`
+/// applies them in order; `describe` serializes the exact steps and
+/// parameters as JSON, e.g. for a regression harness to log next to its
+/// outputs.
+///
+/// ```js
+/// const pipeline = new Pipeline().clahe(8, 8, 2.0).unsharp(1.5, 2).threshold_otsu(false);
+/// const out = pipeline.run(gray, width, height);
+/// console.log(pipeline.describe());
+/// // [{"op":"clahe","tile_grid_x":8,...},{"op":"unsharp",...},{"op":"threshold_otsu","invert":false}]
+/// ```
+///
+/// Every step gives the same result as the free function of the same name.
+/// `run` reuses its working buffers across steps: CLAHE, thresholds and
+/// LUTs work in place and erode / dilate ping-pong between two buffers.
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default)]
+pub struct Pipeline {
+    steps: Vec<Step>,
+}
+
+#[wasm_bindgen]
+impl Pipeline {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Pipeline {
+        Pipeline::default()
+    }
+
+    /// Gaussian `blur`.
+    pub fn blur(mut self, kernel_size: usize, sigma: f32) -> Pipeline {
+        if kernel_size == 0 || kernel_size.is_multiple_of(2) {
+            panic!("Kernel size must be odd and greater than 0");
+        }
+        check_finite(&[sigma]);
+        self.steps.push(Step::Blur { kernel_size, sigma });
+        self
+    }
+
+    /// `clahe` with the given tile grid and clip limit.
+    pub fn clahe(mut self, tile_grid_x: usize, tile_grid_y: usize, clip_limit: f32) -> Pipeline {
+        check_finite(&[clip_limit]);
+        self.steps.push(Step::Clahe { tile_grid_x, tile_grid_y, clip_limit });
+        self
+    }
+
+    /// `unsharp_mask`.
+    pub fn unsharp(mut self, amount: f32, radius: usize) -> Pipeline {
+        check_finite(&[amount]);
+        self.steps.push(Step::Unsharp { amount, radius });
+        self
+    }
+
+    /// Global threshold: 255 above `level`, 0 at or below (swapped with
+    /// `invert`).
+    pub fn threshold(mut self, level: u8, invert: bool) -> Pipeline {
+        self.steps.push(Step::Threshold { level, invert });
+        self
+    }
+
+    /// `threshold` at the `otsu_threshold` level of the image as it reaches
+    /// this step.
+    pub fn threshold_otsu(mut self, invert: bool) -> Pipeline {
+        self.steps.push(Step::ThresholdOtsu { invert });
+        self
+    }
+
+    /// `erode` with a square kernel.
+    pub fn erode(mut self, kernel_size: usize) -> Pipeline {
+        self.steps.push(Step::Erode { kernel_size });
+        self
+    }
+
+    /// `dilate` with a square kernel.
+    pub fn dilate(mut self, kernel_size: usize) -> Pipeline {
+        self.steps.push(Step::Dilate { kernel_size });
+        self
+    }
+
+    /// `apply_lut` with a 256-entry table, e.g. from `build_tone_curve`.
+    pub fn lut(mut self, table: &[u8]) -> Pipeline {
+        if table.len() != 256 {
+            panic!("LUT must have exactly 256 entries");
+        }
+        let mut fixed = Box::new([0u8; 256]);
+        fixed.copy_from_slice(table);
+        self.steps.push(Step::Lut { table: fixed });
+        self
+    }
+
+    /// Number of steps added so far.
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Applies every step in order to a `width` x `height` image. An empty
+    /// pipeline returns a copy of the input.
+    pub fn run(&self, input: &[u8], width: usize, height: usize) -> Vec<u8> {
+        let pixel_count = width * height;
+        if input.len() != pixel_count {
+            panic!("Input array size doesn't match width * height");
+        }
+
+        let mut current = input.to_vec();
+        // Second image buffer and pass scratch for the morphology steps
+        let mut buffers: Option<(Vec<u8>, Vec<u8>)> = None;
+        for step in &self.steps {
+            match step {
+                Step::Blur { kernel_size, sigma } => current = blur(&current, width, height, *kernel_size, *sigma),
+                Step::Clahe { tile_grid_x, tile_grid_y, clip_limit } => {
+                    let grid = TileGrid::new(width, height, *tile_grid_x, *tile_grid_y);
+                    let columns = column_weights(&grid, 0..width);
+                    clahe_inplace_with(&mut current, &grid, &columns, *clip_limit);
+                }
+                Step::Unsharp { amount, radius } => current = unsharp_mask(&current, width, height, *amount, *radius),
+                Step::Threshold { level, invert } => apply_threshold(&mut current, *level, *invert),
+                Step::ThresholdOtsu { invert } => {
+                    let level = otsu_from_histogram(&histogram(&current));
+                    apply_threshold(&mut current, level, *invert);
+                }
+                Step::Erode { kernel_size } | Step::Dilate { kernel_size } => {
+                    let (other, temp) = buffers.get_or_insert_with(|| (vec![0u8; pixel_count], vec![0u8; pixel_count]));
+                    if matches!(step, Step::Erode { .. }) {
+                        erode_into(&current, width, height, *kernel_size, other, temp);
+                    } else {
+                        dilate_into(&current, width, height, *kernel_size, other, temp);
+                    }
+                    std::mem::swap(&mut current, other);
+                }
+                Step::Lut { table } => current.iter_mut().for_each(|v| *v = table[*v as usize]),
+            }
+        }
+
+        current
+    }
+
+    /// The steps and their parameters as a JSON array of objects, one per
+    /// step in order, each with an `op` name and its parameters.
+    pub fn describe(&self) -> String {
+        let steps: Vec<String> = self.steps.iter().map(Step::describe).collect();
+        format!("[{}]", steps.join(","))
+    }
+}
+
+/// Binarizes `data` in place: 255 above `level`, 0 otherwise, or the
+/// reverse with `invert`.
+fn apply_threshold(data: &mut [u8], level: u8, invert: bool) {
+    for v in data.iter_mut() {
+        *v = if (*v > level) != invert { 255 } else { 0 };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clahe::clahe;
+    use crate::dilation::dilate;
+    use crate::histogram::otsu_threshold;
+    use crate::morphology::erode;
+
+    #[test]
+    fn test_pipeline_matches_free_functions() {
+        let (width, height) = (37, 23);
+        let input: Vec<u8> = (0..width * height).map(|i| ((i * 29 + i / width * 7) % 230 + 10) as u8).collect();
+        let pipeline = Pipeline::new().clahe(4, 2, 2.0).unsharp(1.5, 2).erode(3).dilate(5).threshold_otsu(true);
+
+        let mut expected = clahe(&input, width, height, 4, 2, 2.0);
+        expected = unsharp_mask(&expected, width, height, 1.5, 2);
+        expected = dilate(&erode(&expected, width, height, 3), width, height, 5);
+        let level = otsu_threshold(&expected, width, height);
+        expected.iter_mut().for_each(|v| *v = if *v > level { 0 } else { 255 });
+
+        assert_eq!(pipeline.length(), 5);
+        assert_eq!(pipeline.run(&input, width, height), expected);
+        assert_eq!(Pipeline::new().run(&input, width, height), input);
+        assert_eq!(
+            pipeline.describe(),
+            concat!(
+                r#"[{"op":"clahe","tile_grid_x":4,"tile_grid_y":2,"clip_limit":2},"#,
+                r#"{"op":"unsharp","amount":1.5,"radius":2},{"op":"erode","kernel_size":3},"#,
+                r#"{"op":"dilate","kernel_size":5},{"op":"threshold_otsu","invert":true}]"#
+            )
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    /// A JS function called with the completed fraction (`0..=1`) of a
+    /// long-running filter.
+    #[wasm_bindgen(typescript_type = "(fraction: number) => void")]
+    pub type ProgressCallback;
+
+    #[wasm_bindgen(method, js_name = call)]
+    fn call_with(this: &ProgressCallback, this_arg: &JsValue, fraction: f64);
+
+    /// A JS function returning `true` once a long-running filter should stop.
+    /// WASM runs the filter on the calling thread, so cancellation has to be
+    /// polled: typically it reads a flag that the UI sets on re-capture.
+    #[wasm_bindgen(typescript_type = "() => boolean")]
+    pub type CancelCallback;
+
+    #[wasm_bindgen(method, js_name = call)]
+    fn call_check(this: &CancelCallback, this_arg: &JsValue) -> bool;
+}
+
+/// Reports row progress to an optional `ProgressCallback` and polls an
+/// optional `CancelCallback`, at most once per `interval` rows plus once at
+/// the end. Without callbacks every update is a couple of `None` checks.
+pub(crate) struct Progress {
+    callback: Option<ProgressCallback>,
+    cancel: Option<CancelCallback>,
+    cancelled: bool,
+    total: usize,
+    interval: usize,
+    next_report: usize,
+}
+
+impl Progress {
+    pub(crate) fn new(callback: Option<ProgressCallback>, total: usize, interval: usize) -> Progress {
+        let interval = interval.max(1);
+        Progress {
+            callback,
+            cancel: None,
+            cancelled: false,
+            total,
+            interval,
+            next_report: interval,
+        }
+    }
+
+    /// Also polls `cancel` whenever progress is reported.
+    pub(crate) fn with_cancel(mut self, cancel: Option<CancelCallback>) -> Progress {
+        self.cancel = cancel;
+        self
+    }
+
+    /// A reporter that never calls back.
+    pub(crate) fn none() -> Progress {
+        Progress::new(None, 0, 1)
+    }
+
+    /// Records that `done` of `total` rows are finished.
+    #[inline]
+    pub(crate) fn update(&mut self, done: usize) {
+        if self.callback.is_none() && self.cancel.is_none() {
+            return;
+        }
+        if done < self.next_report && done != self.total {
+            return;
+        }
+        self.next_report = done + self.interval;
+        if let Some(callback) = &self.callback {
+            callback.call_with(&JsValue::NULL, done as f64 / self.total.max(1) as f64);
+        }
+        if let Some(cancel) = &self.cancel {
+            self.cancelled = cancel.call_check(&JsValue::NULL);
+        }
+    }
+
+    /// Whether the last poll of the cancel callback asked to stop.
+    #[inline]
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
@@ -0,0 +1,215 @@
+use wasm_bindgen::prelude::*;
+use crate::downscale::resize_bilinear;
+use crate::gaussian_blur::blur;
+
+/// Blur with a 5x5 Gaussian and drop every other row and column, like
+/// OpenCV's `pyrDown`. Output size is `ceil(width / 2) x ceil(height / 2)`.
+pub(crate) fn pyr_down(input: &[u8], width: usize, height: usize) -> (Vec<u8>, usize, usize) {
+    let blurred = blur(input, width, height, 5, 0.0);
+    let out_width = width.div_ceil(2);
+    let out_height = height.div_ceil(2);
+
+    let mut output = vec![0u8; out_width * out_height];
+    for oy in 0..out_height {
+        let src_row = &blurred[2 * oy * width..2 * oy * width + width];
+        let dst_row = &mut output[oy * out_width..(oy + 1) * out_width];
+        for (ox, dst) in dst_row.iter_mut().enumerate() {
+            *dst = src_row[2 * ox];
+        }
+    }
+
+    (output, out_width, out_height)
+}
+
+/// Levels of a Gaussian pyramid. Level 0 is the original image and each
+/// following level is blurred and halved (rounding up) from the previous one.
+#[wasm_bindgen]
+pub struct GaussianPyramid {
+    pub(crate) levels: Vec<Vec<u8>>,
+    pub(crate) widths: Vec<usize>,
+    pub(crate) heights: Vec<usize>,
+}
+
+#[wasm_bindgen]
+impl GaussianPyramid {
+    /// Number of levels, including the original image.
+    #[wasm_bindgen(getter)]
+    pub fn num_levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Pixels of level `index` (row-major, `level_width * level_height` bytes).
+    pub fn level(&self, index: usize) -> Vec<u8> {
+        self.levels[index].clone()
+    }
+
+    pub fn level_width(&self, index: usize) -> usize {
+        self.widths[index]
+    }
+
+    pub fn level_height(&self, index: usize) -> usize {
+        self.heights[index]
+    }
+}
+
+/// Gaussian pyramid with up to `levels` levels (including the original).
+/// Building stops early once a level would shrink to a single pixel in either
+/// dimension, so `num_levels` may be smaller than requested.
+#[wasm_bindgen]
+pub fn gaussian_pyramid(input: &[u8], width: usize, height: usize, levels: usize) -> GaussianPyramid {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if levels == 0 {
+        panic!("Pyramid must have at least one level");
+    }
+
+    let mut pyramid = GaussianPyramid {
+        levels: vec![input.to_vec()],
+        widths: vec![width],
+        heights: vec![height],
+    };
+
+    while pyramid.levels.len() < levels {
+        let last = pyramid.levels.len() - 1;
+        let (w, h) = (pyramid.widths[last], pyramid.heights[last]);
+        if w < 2 || h < 2 {
+            break;
+        }
+        let (next, nw, nh) = pyr_down(&pyramid.levels[last], w, h);
+        pyramid.levels.push(next);
+        pyramid.widths.push(nw);
+        pyramid.heights.push(nh);
+    }
+
+    pyramid
+}
+
+/// Laplacian pyramid: band-pass detail layers plus a low-resolution residual.
+/// Band `i` holds `G[i] - upsample(G[i+1])` as signed values at the size of
+/// Gaussian level `i`; the residual is the coarsest Gaussian level.
+#[wasm_bindgen]
+pub struct LaplacianPyramid {
+    bands: Vec<Vec<i16>>,
+    residual: Vec<u8>,
+    widths: Vec<usize>,
+    heights: Vec<usize>,
+}
+
+#[wasm_bindgen]
+impl LaplacianPyramid {
+    /// Number of detail bands (one less than the number of Gaussian levels).
+    #[wasm_bindgen(getter)]
+    pub fn num_bands(&self) -> usize {
+        self.bands.len()
+    }
+
+    /// Signed detail values of band `index`.
+    pub fn band(&self, index: usize) -> Vec<i16> {
+        self.bands[index].clone()
+    }
+
+    /// Replaces band `index`, e.g. after attenuating or boosting detail in JS.
+    pub fn set_band(&mut self, index: usize, data: &[i16]) {
+        if data.len() != self.bands[index].len() {
+            panic!("Band data size doesn't match the band dimensions");
+        }
+        self.bands[index].copy_from_slice(data);
+    }
+
+    /// Coarsest Gaussian level.
+    #[wasm_bindgen(getter)]
+    pub fn residual(&self) -> Vec<u8> {
+        self.residual.clone()
+    }
+
+    /// Width of band `index`; `index == num_bands` gives the residual width.
+    pub fn level_width(&self, index: usize) -> usize {
+        self.widths[index]
+    }
+
+    /// Height of band `index`; `index == num_bands` gives the residual height.
+    pub fn level_height(&self, index: usize) -> usize {
+        self.heights[index]
+    }
+}
+
+/// Laplacian pyramid built on `gaussian_pyramid` with bilinear upsampling.
+/// `levels` counts Gaussian levels, so the result has `levels - 1` bands plus
+/// the residual (fewer if the image becomes too small).
+#[wasm_bindgen]
+pub fn laplacian_pyramid(input: &[u8], width: usize, height: usize, levels: usize) -> LaplacianPyramid {
+    let gaussian = gaussian_pyramid(input, width, height, levels);
+    let n = gaussian.levels.len();
+
+    let mut bands = Vec::with_capacity(n - 1);
+    for i in 0..n - 1 {
+        let upsampled = resize_bilinear(
+            &gaussian.levels[i + 1],
+            gaussian.widths[i + 1],
+            gaussian.heights[i + 1],
+            gaussian.widths[i],
+            gaussian.heights[i],
+        );
+        let band = gaussian.levels[i]
+            .iter()
+            .zip(upsampled.iter())
+            .map(|(&g, &u)| g as i16 - u as i16)
+            .collect();
+        bands.push(band);
+    }
+
+    LaplacianPyramid {
+        bands,
+        residual: gaussian.levels[n - 1].clone(),
+        widths: gaussian.widths,
+        heights: gaussian.heights,
+    }
+}
+
+/// Collapses a Laplacian pyramid back into an image by repeatedly upsampling
+/// and adding the next band. With unmodified bands the result equals the
+/// original input exactly, since each band was computed against the same
+/// deterministic upsampling.
+#[wasm_bindgen]
+pub fn reconstruct_from_laplacian(pyramid: &LaplacianPyramid) -> Vec<u8> {
+    let n = pyramid.bands.len();
+    let mut current = pyramid.residual.clone();
+
+    for i in (0..n).rev() {
+        let upsampled = resize_bilinear(
+            &current,
+            pyramid.widths[i + 1],
+            pyramid.heights[i + 1],
+            pyramid.widths[i],
+            pyramid.heights[i],
+        );
+        current = upsampled
+            .iter()
+            .zip(pyramid.bands[i].iter())
+            .map(|(&u, &d)| (u as i16 + d).clamp(0, 255) as u8)
+            .collect();
+    }
+
+    current
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_laplacian_roundtrip_is_lossless() {
+        // Odd dimensions exercise the rounding-up of level sizes
+        let width = 37;
+        let height = 23;
+        let input: Vec<u8> = (0..width * height).map(|i| ((i * 7919) % 251) as u8).collect();
+
+        let pyramid = laplacian_pyramid(&input, width, height, 4);
+        assert_eq!(pyramid.num_bands(), 3);
+        assert_eq!(pyramid.level_width(3), 5);
+        assert_eq!(pyramid.level_height(3), 3);
+
+        assert_eq!(reconstruct_from_laplacian(&pyramid), input);
+    }
+}
@@ -0,0 +1,244 @@
+use wasm_bindgen::prelude::*;
+use crate::canny::canny_edge_detector_full;
+
+/// Document corners together with how much the detection can be trusted.
+#[wasm_bindgen]
+pub struct DocumentCorners {
+    corners: Vec<f32>,
+    confidence: f32,
+}
+
+#[wasm_bindgen]
+impl DocumentCorners {
+    /// `[x0, y0, x1, y1, x2, y2, x3, y3]` as passed to `score_document_corners`.
+    #[wasm_bindgen(getter)]
+    pub fn corners(&self) -> Vec<f32> {
+        self.corners.clone()
+    }
+
+    /// Confidence in [0, 1]; 0 for degenerate or non-convex quads.
+    #[wasm_bindgen(getter)]
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+}
+
+/// Fraction of the samples along the segment (x0, y0)-(x1, y1) that have an
+/// edge pixel within `reach` pixels.
+fn edge_support(edges: &[u8], width: usize, height: usize, from: (f32, f32), to: (f32, f32), reach: usize) -> f32 {
+    let length = (to.0 - from.0).hypot(to.1 - from.1);
+    let samples = length.ceil().max(1.0) as usize;
+    let mut supported = 0usize;
+    for s in 0..=samples {
+        let t = s as f32 / samples as f32;
+        let x = (from.0 + t * (to.0 - from.0)).round().clamp(0.0, (width - 1) as f32) as usize;
+        let y = (from.1 + t * (to.1 - from.1)).round().clamp(0.0, (height - 1) as f32) as usize;
+        let hit = (y.saturating_sub(reach)..(y + reach + 1).min(height))
+            .any(|ny| (x.saturating_sub(reach)..(x + reach + 1).min(width)).any(|nx| edges[ny * width + nx] != 0));
+        if hit {
+            supported += 1;
+        }
+    }
+    supported as f32 / (samples + 1) as f32
+}
+
+/// Confidence of a detected document quadrilateral in [0, 1], for gating
+/// auto-capture. `corners` are the four corners in order around the quad
+/// (either direction) as `[x0, y0, ..., x3, y3]`, in the coordinates of the
+/// binary `edges` map the detection ran on.
+///
+/// Three geometric cues are combined:
+/// * area - quads covering under 5% of the image score 0, rising to 1 at 30%;
+///   quads hugging the whole frame (over 98%) are usually the image border and score 0
+/// * angles - 1 when every corner is 90 degrees, falling to 0 at 45 degrees off
+/// * edge support - fraction of points along the four sides with an edge
+///   pixel within 2 pixels, averaged with the weakest side so one missing
+///   side is penalized
+///
+/// The result is `0.2 * area + 0.3 * angles + 0.5 * edges`, or 0 when the quad
+/// is self-intersecting, non-convex or degenerate.
+#[wasm_bindgen]
+pub fn score_document_corners(corners: &[f32], edges: &[u8], width: usize, height: usize) -> DocumentCorners {
+    if corners.len() != 8 {
+        panic!("Corners must hold four points (8 coordinates)");
+    }
+    if edges.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let confidence = quad_confidence(corners, edges, width, height);
+    DocumentCorners { corners: corners.to_vec(), confidence }
+}
+
+fn quad_confidence(corners: &[f32], edges: &[u8], width: usize, height: usize) -> f32 {
+    if width == 0 || height == 0 {
+        return 0.0;
+    }
+    let p: Vec<(f32, f32)> = corners.chunks_exact(2).map(|c| (c[0], c[1])).collect();
+
+    // Convexity: all turns in the same direction
+    let cross = |i: usize| {
+        let (a, b, c) = (p[i], p[(i + 1) % 4], p[(i + 2) % 4]);
+        (b.0 - a.0) * (c.1 - b.1) - (b.1 - a.1) * (c.0 - b.0)
+    };
+    let turns = [cross(0), cross(1), cross(2), cross(3)];
+    if !(turns.iter().all(|&t| t > 0.0) || turns.iter().all(|&t| t < 0.0)) {
+        return 0.0;
+    }
+
+    let area = 0.5 * (0..4).map(|i| p[i].0 * p[(i + 1) % 4].1 - p[(i + 1) % 4].0 * p[i].1).sum::<f32>().abs();
+    let ratio = area / (width * height) as f32;
+    let area_score = if ratio > 0.98 { 0.0 } else { ((ratio - 0.05) / 0.25).clamp(0.0, 1.0) };
+
+    let mut max_deviation = 0.0f32;
+    for i in 0..4 {
+        let (prev, cur, next) = (p[(i + 3) % 4], p[i], p[(i + 1) % 4]);
+        let (ax, ay) = (prev.0 - cur.0, prev.1 - cur.1);
+        let (bx, by) = (next.0 - cur.0, next.1 - cur.1);
+        let angle = (ax * by - ay * bx).abs().atan2(ax * bx + ay * by).to_degrees();
+        max_deviation = max_deviation.max((angle - 90.0).abs());
+    }
+    let angle_score = (1.0 - max_deviation / 45.0).clamp(0.0, 1.0);
+
+    let sides: Vec<f32> = (0..4).map(|i| edge_support(edges, width, height, p[i], p[(i + 1) % 4], 2)).collect();
+    let mean = sides.iter().sum::<f32>() / 4.0;
+    let weakest = sides.iter().copied().fold(1.0, f32::min);
+    let edge_score = 0.5 * (mean + weakest);
+
+    0.2 * area_score + 0.3 * angle_score + 0.5 * edge_score
+}
+
+/// Everything `analyze_document` finds in one frame.
+#[wasm_bindgen]
+pub struct DocumentAnalysis {
+    edges: Vec<u8>,
+    contour: Vec<u32>,
+    corners: Vec<f32>,
+    confidence: f32,
+}
+
+#[wasm_bindgen]
+impl DocumentAnalysis {
+    /// Binary Canny edge map (0 / 255) the detection ran on.
+    #[wasm_bindgen(getter)]
+    pub fn edges(&self) -> Vec<u8> {
+        self.edges.clone()
+    }
+
+    /// Outline of the document candidate as `[x0, y0, x1, y1, ...]`,
+    /// clockwise on screen; empty when no edges were found.
+    #[wasm_bindgen(getter)]
+    pub fn contour(&self) -> Vec<u32> {
+        self.contour.clone()
+    }
+
+    /// `[x0, y0, ..., x3, y3]` in top-left, top-right, bottom-right,
+    /// bottom-left order; empty when no edges were found.
+    #[wasm_bindgen(getter)]
+    pub fn corners(&self) -> Vec<f32> {
+        self.corners.clone()
+    }
+
+    /// Confidence in [0, 1] as computed by `score_document_corners`.
+    #[wasm_bindgen(getter)]
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+}
+
+/// Pixels of the 8-connected edge component with the largest bounding box.
+fn largest_component(edges: &[u8], width: usize, height: usize) -> Vec<(i64, i64)> {
+    let mut visited = vec![false; width * height];
+    let mut best: (usize, Vec<(i64, i64)>) = (0, Vec::new());
+    let mut stack = Vec::new();
+    for start in 0..width * height {
+        if visited[start] || edges[start] == 0 {
+            continue;
+        }
+        visited[start] = true;
+        stack.push(start);
+        let mut pixels = Vec::new();
+        let (mut x0, mut y0, mut x1, mut y1) = (width, height, 0, 0);
+        while let Some(idx) = stack.pop() {
+            let (x, y) = (idx % width, idx / width);
+            pixels.push((x as i64, y as i64));
+            (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x), y1.max(y));
+            for ny in y.saturating_sub(1)..(y + 2).min(height) {
+                for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                    let nidx = ny * width + nx;
+                    if !visited[nidx] && edges[nidx] != 0 {
+                        visited[nidx] = true;
+                        stack.push(nidx);
+                    }
+                }
+            }
+        }
+        let box_area = (x1 - x0 + 1) * (y1 - y0 + 1);
+        if box_area > best.0 {
+            best = (box_area, pixels);
+        }
+    }
+    best.1
+}
+
+/// Convex hull (Andrew's monotone chain), clockwise on screen starting from
+/// the leftmost point.
+fn convex_hull(mut points: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    points.sort_unstable();
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let cross = |o: (i64, i64), a: (i64, i64), b: (i64, i64)| (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0);
+    let mut hull: Vec<(i64, i64)> = Vec::with_capacity(2 * points.len());
+    for pass in [points.as_slice(), &points.iter().rev().copied().collect::<Vec<_>>()] {
+        let floor = hull.len();
+        for &p in pass {
+            while hull.len() >= floor + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0 {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+/// Canny edges, document outline, corners and confidence in a single call,
+/// so a per-frame scanner loop crosses the JS / WASM boundary once. Each
+/// stage is available separately as well.
+///
+/// * edges - `canny_edge_detector_full` with thresholds 20 / 50 (on its
+///   central-difference gradients), a 5x5 Gaussian and a 3x3 dilation
+/// * contour - convex hull of the edge component with the largest bounding
+///   box, which on a document photo is the page outline
+/// * corners - hull points that are extreme along the two diagonals
+///   (smallest and largest `x + y` and `x - y`)
+/// * confidence - `score_document_corners` of those corners on the edge map
+#[wasm_bindgen]
+pub fn analyze_document(input: &[u8], width: usize, height: usize) -> DocumentAnalysis {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if width < 3 || height < 3 {
+        return DocumentAnalysis { edges: vec![0; width * height], contour: Vec::new(), corners: Vec::new(), confidence: 0.0 };
+    }
+
+    let edges = canny_edge_detector_full(input, width, height, 20.0, 50.0, 5, 0.0, false, true, 3);
+    let hull = convex_hull(largest_component(&edges, width, height));
+    if hull.is_empty() {
+        return DocumentAnalysis { edges, contour: Vec::new(), corners: Vec::new(), confidence: 0.0 };
+    }
+
+    let extreme = |key: &dyn Fn(&(i64, i64)) -> i64| *hull.iter().min_by_key(|p| key(p)).unwrap();
+    let quad = [
+        extreme(&|p| p.0 + p.1),
+        extreme(&|p| p.1 - p.0),
+        extreme(&|p| -(p.0 + p.1)),
+        extreme(&|p| p.0 - p.1),
+    ];
+    let corners: Vec<f32> = quad.iter().flat_map(|&(x, y)| [x as f32, y as f32]).collect();
+    let confidence = quad_confidence(&corners, &edges, width, height);
+    let contour = hull.iter().flat_map(|&(x, y)| [x as u32, y as u32]).collect();
+    DocumentAnalysis { edges, contour, corners, confidence }
+}
@@ -0,0 +1,217 @@
+use wasm_bindgen::prelude::*;
+
+/// Pixel steps of the four directions `estimate_motion_blur` measures the
+/// blur extent along: 0, 45, 90 and 135 degrees (clockwise on screen).
+const BLUR_STEPS: [(isize, isize); 4] = [(1, 0), (1, 1), (0, 1), (-1, 1)];
+
+/// Estimated direction and length of motion blur, from the anisotropy of
+/// the image gradients. Blurring along a direction wipes out the gradients
+/// along it and leaves those across it. The direction is the weak axis of
+/// the structure tensor summed over the whole image (central differences,
+/// no smoothing). The length comes from the energy of central differences
+/// along and across the nearest multiple of 45 degrees, taken in whole
+/// pixel steps so diagonal blur is measured like axis-aligned blur: for a
+/// box blur of `L` pixels over fine texture the across / along ratio is
+/// `L / 2`, and the reported extent `2 * (ratio - 1)` is then `L - 2`
+/// (blur over one or two pixels is indistinguishable from sharp).
+///
+/// This is a rough, global estimate meant for gating captures ("hold
+/// steady"), not for deconvolution: strongly oriented content such as
+/// ruled lines or a single text column also reads as anisotropic.
+///
+/// # Returns
+/// `[angle, extent]`: the blur direction in degrees in [0, 180), 0 being
+/// horizontal and angles increasing clockwise on screen (y points down),
+/// and the approximate blur length in pixels beyond the first two, near 0
+/// for a sharp image. The extent is also 0 when the image has no gradient
+/// at all along the blur direction, where it cannot be measured.
+#[wasm_bindgen]
+pub fn estimate_motion_blur(input: &[u8], width: usize, height: usize) -> Vec<f32> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if width < 3 || height < 3 {
+        return vec![0.0, 0.0];
+    }
+
+    let at = |x: usize, y: usize, (dx, dy): (isize, isize)| {
+        input[(y as isize + dy) as usize * width + (x as isize + dx) as usize] as f64
+    };
+    let (mut jxx, mut jyy, mut jxy) = (0.0f64, 0.0f64, 0.0f64);
+    let mut energies = [0.0f64; 4];
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            for (energy, &(dx, dy)) in energies.iter_mut().zip(&BLUR_STEPS) {
+                let d = at(x, y, (dx, dy)) - at(x, y, (-dx, -dy));
+                *energy += d * d / (dx * dx + dy * dy) as f64;
+            }
+            let gx = (at(x, y, (1, 0)) - at(x, y, (-1, 0))) / 2.0;
+            let gy = (at(x, y, (0, 1)) - at(x, y, (0, -1))) / 2.0;
+            jxx += gx * gx;
+            jyy += gy * gy;
+            jxy += gx * gy;
+        }
+    }
+    if jxx + jyy <= 0.0 {
+        return vec![0.0, 0.0];
+    }
+
+    // The strong axis follows the surviving gradients, across the blur
+    let gradient_angle = 0.5 * (2.0 * jxy).atan2(jxx - jyy);
+    let angle = (gradient_angle.to_degrees() + 90.0).rem_euclid(180.0);
+
+    let along = (angle / 45.0).round() as usize % 4;
+    let across = (along + 2) % 4;
+    // No gradient along the blur axis leaves nothing to measure the
+    // extent against (e.g. pure stripes)
+    let extent = if energies[along] > 0.0 {
+        (2.0 * (energies[across] / energies[along] - 1.0)).max(0.0)
+    } else {
+        0.0
+    };
+
+    vec![angle as f32, extent as f32]
+}
+
+/// Levels within this distance of 0 or 255 count as clipped for
+/// `CaptureQuality::exposure`; compressed camera frames rarely hit the
+/// extremes exactly.
+const CLIP_MARGIN: u8 = 2;
+
+/// Capture diagnostics for a live "ready to capture" indicator, gathered in
+/// one pass by `capture_quality`.
+#[wasm_bindgen]
+pub struct CaptureQuality {
+    focus: f64,
+    exposure: f64,
+    contrast: f64,
+}
+
+#[wasm_bindgen]
+impl CaptureQuality {
+    /// Variance of the 4-neighbour Laplacian over the interior pixels:
+    /// higher is sharper. Depends on content and resolution, so compare it
+    /// against a threshold tuned for a fixed preview size.
+    #[wasm_bindgen(getter)]
+    pub fn focus(&self) -> f64 {
+        self.focus
+    }
+
+    /// Fraction of pixels clipped to black or white (0..1); large values
+    /// mean blown highlights or crushed shadows.
+    #[wasm_bindgen(getter)]
+    pub fn exposure(&self) -> f64 {
+        self.exposure
+    }
+
+    /// Dynamic range in levels (0..255): the spread between the 1st and
+    /// 99th percentile, so a few outliers don't count.
+    #[wasm_bindgen(getter)]
+    pub fn contrast(&self) -> f64 {
+        self.contrast
+    }
+}
+
+/// Focus, exposure and contrast of a capture in a single pass over the
+/// image, so a live preview needs one call per frame instead of three. See
+/// the `CaptureQuality` getters for what each value measures.
+#[wasm_bindgen]
+pub fn capture_quality(input: &[u8], width: usize, height: usize) -> CaptureQuality {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let mut hist = [0u32; 256];
+    let (mut sum, mut sum_sq, mut count) = (0.0f64, 0.0f64, 0u64);
+    for y in 0..height {
+        let row = &input[y * width..(y + 1) * width];
+        for &v in row {
+            hist[v as usize] += 1;
+        }
+        if y == 0 || y + 1 >= height {
+            continue;
+        }
+        for x in 1..width.saturating_sub(1) {
+            let idx = y * width + x;
+            let laplacian = input[idx - 1] as f64
+                + input[idx + 1] as f64
+                + input[idx - width] as f64
+                + input[idx + width] as f64
+                - 4.0 * input[idx] as f64;
+            sum += laplacian;
+            sum_sq += laplacian * laplacian;
+            count += 1;
+        }
+    }
+
+    let total = (width * height) as f64;
+    if total == 0.0 {
+        return CaptureQuality { focus: 0.0, exposure: 0.0, contrast: 0.0 };
+    }
+    let focus = if count > 0 {
+        let mean = sum / count as f64;
+        sum_sq / count as f64 - mean * mean
+    } else {
+        0.0
+    };
+
+    let clipped: u32 = hist[..=CLIP_MARGIN as usize].iter().chain(&hist[(255 - CLIP_MARGIN) as usize..]).sum();
+    let percentile = |fraction: f64| {
+        let target = (total * fraction).ceil().max(1.0) as u32;
+        let mut running = 0;
+        hist.iter()
+            .position(|&c| {
+                running += c;
+                running >= target
+            })
+            .unwrap_or(255) as f64
+    };
+
+    CaptureQuality {
+        focus,
+        exposure: clipped as f64 / total,
+        contrast: percentile(0.99) - percentile(0.01),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_motion_blur_direction_and_extent() {
+        let (width, height) = (120, 90);
+        let mut seed = 12345u32;
+        let noise: Vec<f32> = (0..width * height)
+            .map(|_| {
+                seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                (seed >> 24) as f32
+            })
+            .collect();
+
+        // 9-pixel box blur along each direction, wrapping at the borders
+        for (step, expected_angle) in [((1isize, 0isize), 0.0f32), ((0, 1), 90.0), ((1, 1), 45.0)] {
+            let blurred: Vec<u8> = (0..width * height)
+                .map(|i| {
+                    let (x, y) = ((i % width) as isize, (i / width) as isize);
+                    let sum: f32 = (0..9)
+                        .map(|k| {
+                            let sx = (x + k * step.0).rem_euclid(width as isize) as usize;
+                            let sy = (y + k * step.1).rem_euclid(height as isize) as usize;
+                            noise[sy * width + sx]
+                        })
+                        .sum();
+                    (sum / 9.0).round() as u8
+                })
+                .collect();
+
+            let estimate = estimate_motion_blur(&blurred, width, height);
+            let angle_error = (estimate[0] - expected_angle).rem_euclid(180.0);
+            assert!(angle_error.min(180.0 - angle_error) < 2.0, "{:?}", estimate);
+            assert!((estimate[1] - 7.0).abs() < 1.5, "{:?}", estimate);
+        }
+
+        let sharp: Vec<u8> = noise.iter().map(|&v| v as u8).collect();
+        assert!(estimate_motion_blur(&sharp, width, height)[1] < 0.5);
+    }
+}
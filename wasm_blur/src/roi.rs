@@ -0,0 +1,150 @@
+use wasm_bindgen::prelude::*;
+
+/// Checks that the rectangle at (`x`, `y`) of size `roi_width` x `roi_height`
+/// lies inside a `width` x `height` image.
+pub(crate) fn check_roi(width: usize, height: usize, x: usize, y: usize, roi_width: usize, roi_height: usize) {
+    if x + roi_width > width || y + roi_height > height {
+        panic!("ROI extends beyond the image");
+    }
+}
+
+/// Copies the rectangle at (`x`, `y`) of size `crop_width` x `crop_height`
+/// into a tightly packed buffer.
+pub(crate) fn crop(
+    input: &[u8],
+    width: usize,
+    x: usize,
+    y: usize,
+    crop_width: usize,
+    crop_height: usize,
+) -> Vec<u8> {
+    let mut output = Vec::with_capacity(crop_width * crop_height);
+    for row in y..y + crop_height {
+        output.extend_from_slice(&input[row * width + x..row * width + x + crop_width]);
+    }
+    output
+}
+
+/// Writes the tightly packed `patch_width` x `patch_height` `patch` into
+/// `image` at (`x`, `y`).
+pub(crate) fn paste(
+    image: &mut [u8],
+    width: usize,
+    x: usize,
+    y: usize,
+    patch: &[u8],
+    patch_width: usize,
+    patch_height: usize,
+) {
+    for row in 0..patch_height {
+        let dst = (y + row) * width + x;
+        image[dst..dst + patch_width].copy_from_slice(&patch[row * patch_width..(row + 1) * patch_width]);
+    }
+}
+
+/// Shortest run of consecutive off-background pixels that makes a row or
+/// column count as content for `trim_margins`; shorter runs are speckle.
+const TRIM_MIN_RUN: usize = 3;
+
+/// Whether `line` has `min_run` consecutive pixels differing from
+/// `background` by more than `threshold`.
+fn has_content_run(line: impl Iterator<Item = u8>, background: u8, threshold: u8, min_run: usize) -> bool {
+    let mut run = 0;
+    for v in line {
+        run = if v.abs_diff(background) > threshold { run + 1 } else { 0 };
+        if run >= min_run {
+            return true;
+        }
+    }
+    false
+}
+
+/// An image with its margins removed, and where it sat in the original.
+#[wasm_bindgen]
+pub struct TrimResult {
+    image: Vec<u8>,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+#[wasm_bindgen]
+impl TrimResult {
+    /// The trimmed image, `width * height` bytes.
+    #[wasm_bindgen(getter)]
+    pub fn image(&self) -> Vec<u8> {
+        self.image.clone()
+    }
+
+    /// Left edge of the trimmed image in the original.
+    #[wasm_bindgen(getter)]
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    /// Top edge of the trimmed image in the original.
+    #[wasm_bindgen(getter)]
+    pub fn y(&self) -> usize {
+        self.y
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
+
+/// Removes uniform margins: scans inward from each edge and drops rows and
+/// columns until one holds content. The background level is the median of
+/// the outermost pixels, and a pixel is off-background when it differs from
+/// it by more than `threshold`. Unlike a bounding box of every such pixel,
+/// a row or column only counts as content when it has a run of at least
+/// three consecutive off-background pixels, so isolated dust and scanner
+/// speckle in the margins don't stop the trim. An image without content is
+/// returned whole.
+#[wasm_bindgen]
+pub fn trim_margins(input: &[u8], width: usize, height: usize, threshold: u8) -> TrimResult {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    let whole = || TrimResult { image: input.to_vec(), x: 0, y: 0, width, height };
+    if width == 0 || height == 0 {
+        return whole();
+    }
+
+    let mut frame: Vec<u8> = input[..width].to_vec();
+    frame.extend_from_slice(&input[(height - 1) * width..]);
+    frame.extend((1..height - 1).flat_map(|y| [input[y * width], input[y * width + width - 1]]));
+    frame.sort_unstable();
+    let background = frame[frame.len() / 2];
+
+    // Runs are capped at the length of the line being scanned
+    let row_run = TRIM_MIN_RUN.min(width);
+    let row_content =
+        |y: usize| has_content_run(input[y * width..(y + 1) * width].iter().copied(), background, threshold, row_run);
+
+    let Some(top) = (0..height).find(|&y| row_content(y)) else {
+        return whole();
+    };
+    let bottom = (top..height).rev().find(|&y| row_content(y)).unwrap_or(top);
+    let column_run = TRIM_MIN_RUN.min(bottom - top + 1);
+    let column_content =
+        |x: usize| has_content_run((top..=bottom).map(|y| input[y * width + x]), background, threshold, column_run);
+    let left = (0..width).find(|&x| column_content(x)).unwrap_or(0);
+    let right = (left..width).rev().find(|&x| column_content(x)).unwrap_or(width - 1);
+
+    let (trim_width, trim_height) = (right - left + 1, bottom - top + 1);
+    TrimResult {
+        image: crop(input, width, left, top, trim_width, trim_height),
+        x: left,
+        y: top,
+        width: trim_width,
+        height: trim_height,
+    }
+}
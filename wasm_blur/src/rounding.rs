@@ -0,0 +1,71 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use wasm_bindgen::prelude::*;
+
+/// How filters convert their floating-point results to `u8` pixels.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundMode {
+    /// Round to nearest, halves away from zero (`2.5 -> 3`); the default
+    Round,
+    /// Truncate towards negative infinity (`2.7 -> 2`), as some OpenCV paths do
+    Floor,
+    /// Round to nearest, halves to the even neighbour (`2.5 -> 2`, `3.5 -> 4`)
+    HalfEven,
+}
+
+static ROUND_MODE: AtomicU8 = AtomicU8::new(RoundMode::Round as u8);
+
+impl RoundMode {
+    /// The mode selected with `set_round_mode`.
+    #[inline]
+    pub(crate) fn current() -> RoundMode {
+        match ROUND_MODE.load(Ordering::Relaxed) {
+            1 => RoundMode::Floor,
+            2 => RoundMode::HalfEven,
+            _ => RoundMode::Round,
+        }
+    }
+
+    /// Converts `value` to a pixel, saturating to `0..=255`.
+    #[inline]
+    pub(crate) fn to_u8(self, value: f32) -> u8 {
+        let rounded = match self {
+            RoundMode::Round => value.round(),
+            RoundMode::Floor => value.floor(),
+            RoundMode::HalfEven => value.round_ties_even(),
+        };
+        rounded.clamp(0.0, 255.0) as u8
+    }
+}
+
+/// Selects the float-to-`u8` rounding used by the intensity-remapping
+/// filters (CLAHE and its variants, unsharp mask, convolution and the
+/// bilinear resizes) for all later calls, e.g. `RoundMode.Floor` when diffing
+/// pixel-for-pixel against a reference implementation that truncates.
+#[wasm_bindgen]
+pub fn set_round_mode(mode: RoundMode) {
+    ROUND_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// The rounding mode currently in effect (`RoundMode.Round` unless changed).
+#[wasm_bindgen]
+pub fn round_mode() -> RoundMode {
+    RoundMode::current()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modes_differ_only_on_ties_and_fractions() {
+        assert_eq!(RoundMode::Round.to_u8(2.5), 3);
+        assert_eq!(RoundMode::Floor.to_u8(2.7), 2);
+        assert_eq!(RoundMode::HalfEven.to_u8(2.5), 2);
+        assert_eq!(RoundMode::HalfEven.to_u8(3.5), 4);
+        for mode in [RoundMode::Round, RoundMode::Floor, RoundMode::HalfEven] {
+            assert_eq!(mode.to_u8(-4.0), 0);
+            assert_eq!(mode.to_u8(300.0), 255);
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use wasm_bindgen::prelude::*;
+use crate::histogram::{histogram, otsu_from_histogram};
+use crate::integral_image::IntegralImage;
+
+/// Single-pass mean / standard deviation / min / max over a rectangle.
+fn region_stats(
+    input: &[u8],
+    width: usize,
+    x0: usize,
+    y0: usize,
+    region_width: usize,
+    region_height: usize,
+) -> Vec<f64> {
+    let count = region_width * region_height;
+    if count == 0 {
+        return vec![0.0, 0.0, 0.0, 0.0];
+    }
+
+    let mut sum = 0u64;
+    let mut sum_sq = 0u64;
+    let mut min_val = 255u8;
+    let mut max_val = 0u8;
+    for y in y0..y0 + region_height {
+        let row = &input[y * width + x0..y * width + x0 + region_width];
+        for &v in row {
+            sum += v as u64;
+            sum_sq += (v as u64) * (v as u64);
+            min_val = min_val.min(v);
+            max_val = max_val.max(v);
+        }
+    }
+
+    // Integer accumulators keep the E[x^2] - E[x]^2 form exact for u8 data
+    let n = count as f64;
+    let mean = sum as f64 / n;
+    let variance = (sum_sq as f64 / n - mean * mean).max(0.0);
+
+    vec![mean, variance.sqrt(), min_val as f64, max_val as f64]
+}
+
+/// Mean, (population) standard deviation, minimum and maximum in one pass.
+///
+/// # Returns
+/// `[mean, std_dev, min, max]` as a Float64Array
+#[wasm_bindgen]
+pub fn image_stats(input: &[u8], width: usize, height: usize) -> Vec<f64> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    region_stats(input, width, 0, 0, width, height)
+}
+
+/// Same as `image_stats`, restricted to the rectangle at (`x`, `y`) of size
+/// `region_width` x `region_height`. The rectangle is clipped to the image.
+#[wasm_bindgen]
+pub fn image_stats_region(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+    region_width: usize,
+    region_height: usize,
+) -> Vec<f64> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    let x0 = x.min(width);
+    let y0 = y.min(height);
+    let w = region_width.min(width - x0);
+    let h = region_height.min(height - y0);
+    region_stats(input, width, x0, y0, w, h)
+}
+
+/// Shannon entropy of the intensity histogram, `-sum(p * log2 p)`, in bits.
+/// Ranges from 0 (a single flat value) to 8 (all 256 levels equally likely);
+/// blank or badly defocused captures score low.
+#[wasm_bindgen]
+pub fn image_entropy(input: &[u8], width: usize, height: usize) -> f64 {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+    if pixel_count == 0 {
+        return 0.0;
+    }
+
+    histogram_entropy(&histogram(input))
+}
+
+/// Shannon entropy in bits of a histogram; 0 when it is empty.
+pub(crate) fn histogram_entropy(hist: &[u32; 256]) -> f64 {
+    let n: f64 = hist.iter().map(|&c| c as f64).sum();
+    let mut entropy = 0.0;
+    for &count in hist.iter() {
+        if count > 0 {
+            let p = count as f64 / n;
+            entropy -= p * p.log2();
+        }
+    }
+
+    entropy
+}
+
+/// Otsu classes whose means differ by less than this are treated as one
+/// class (paper grain and sensor noise rather than content).
+const MIN_CONTENT_CONTRAST: f64 = 32.0;
+
+/// Fraction of the image covered by content, in [0, 1]: the smaller of the
+/// two Otsu classes, which is the ink on a page of either polarity. A page
+/// whose classes differ by less than 32 intensity levels on average is
+/// treated as uniform and scores 0, so noise on blank paper does not count.
+#[wasm_bindgen]
+pub fn content_fraction(input: &[u8], width: usize, height: usize) -> f64 {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+    if pixel_count == 0 {
+        return 0.0;
+    }
+
+    let hist = histogram(input);
+    let level = otsu_from_histogram(&hist) as usize;
+    let (below, above) = hist.split_at(level + 1);
+    let count = |h: &[u32]| h.iter().map(|&c| c as f64).sum::<f64>();
+    let weighted = |h: &[u32], offset: usize| {
+        h.iter().enumerate().map(|(v, &c)| (v + offset) as f64 * c as f64).sum::<f64>()
+    };
+    let (n_below, n_above) = (count(below), count(above));
+    if n_below == 0.0 || n_above == 0.0 {
+        return 0.0;
+    }
+    let contrast = weighted(above, level + 1) / n_above - weighted(below, 0) / n_below;
+    if contrast < MIN_CONTENT_CONTRAST {
+        return 0.0;
+    }
+
+    n_below.min(n_above) / pixel_count as f64
+}
+
+/// True when the page is blank: its `content_fraction` is below `threshold`.
+/// Even sparse text covers a few tenths of a percent of a page, so small
+/// thresholds (0.001 - 0.002) are a good starting point for dropping empty
+/// page backs from a batch.
+#[wasm_bindgen]
+pub fn is_blank(input: &[u8], width: usize, height: usize, threshold: f32) -> bool {
+    content_fraction(input, width, height) < threshold as f64
+}
+
+/// Zero-mean, high-passed copy of a profile: each value minus the mean of
+/// its +/-4 neighbourhood, so slow trends don't dominate the autocorrelation.
+fn detrend(profile: &[f64]) -> Vec<f64> {
+    let n = profile.len();
+    let mut result: Vec<f64> = (0..n)
+        .map(|i| {
+            let lo = i.saturating_sub(4);
+            let hi = (i + 4).min(n - 1);
+            let local = profile[lo..=hi].iter().sum::<f64>() / (hi - lo + 1) as f64;
+            profile[i] - local
+        })
+        .collect();
+    let mean = result.iter().sum::<f64>() / n as f64;
+    result.iter_mut().for_each(|v| *v -= mean);
+    result
+}
+
+/// Highest normalized autocorrelation of a profile over lags `2..=len / 2`
+/// (capped at 64), or 0 when the profile is too short or flat.
+fn peak_autocorrelation(profile: &[f64]) -> f64 {
+    if profile.len() < 8 {
+        return 0.0;
+    }
+    let h = detrend(profile);
+    let energy: f64 = h.iter().map(|v| v * v).sum();
+    if energy <= 1e-9 {
+        return 0.0;
+    }
+
+    let max_lag = (h.len() / 2).min(64);
+    let mut peak = 0.0f64;
+    for lag in 2..=max_lag {
+        let r: f64 = h[..h.len() - lag].iter().zip(&h[lag..]).map(|(a, b)| a * b).sum();
+        // Rescale for the shorter overlap so long lags aren't penalized
+        let r = r / energy * h.len() as f64 / (h.len() - lag) as f64;
+        peak = peak.max(r);
+    }
+    peak.min(1.0)
+}
+
+/// Aliasing score for periodic grid / checkerboard artifacts, e.g. from
+/// duplicated or dropped source columns in a nearest-neighbour downscale.
+///
+/// Builds the mean horizontal gradient magnitude of every column and the mean
+/// vertical gradient magnitude of every row, and returns the strongest
+/// autocorrelation peak of either profile at lags of 2 or more pixels.
+/// Periodic artifacts score close to 1; natural images usually stay well
+/// below 0.5. Patterns with a period of one or two pixels in both directions
+/// (a pixel-level checkerboard) average out and are not detected.
+#[wasm_bindgen]
+pub fn detect_periodic_artifacts(input: &[u8], width: usize, height: usize) -> f64 {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if width < 2 || height < 2 {
+        return 0.0;
+    }
+
+    let mut column_profile = vec![0.0f64; width - 1];
+    let mut row_profile = vec![0.0f64; height - 1];
+    for y in 0..height {
+        let row = &input[y * width..(y + 1) * width];
+        for x in 0..width - 1 {
+            column_profile[x] += (row[x + 1] as f64 - row[x] as f64).abs();
+        }
+        if y + 1 < height {
+            let next = &input[(y + 1) * width..(y + 2) * width];
+            row_profile[y] = row
+                .iter()
+                .zip(next)
+                .map(|(&a, &b)| (b as f64 - a as f64).abs())
+                .sum::<f64>();
+        }
+    }
+
+    peak_autocorrelation(&column_profile).max(peak_autocorrelation(&row_profile))
+}
+
+/// Mean squared error between two grayscale images of the same size.
+#[wasm_bindgen]
+pub fn mse(a: &[u8], b: &[u8], width: usize, height: usize) -> f64 {
+    let pixel_count = width * height;
+    if a.len() != pixel_count || b.len() != pixel_count {
+        panic!("Input array sizes don't match width * height");
+    }
+    if pixel_count == 0 {
+        return 0.0;
+    }
+
+    let sum: u64 = a
+        .iter()
+        .zip(b)
+        .map(|(&x, &y)| {
+            let d = x as i64 - y as i64;
+            (d * d) as u64
+        })
+        .sum();
+    sum as f64 / pixel_count as f64
+}
+
+/// Peak signal-to-noise ratio `10 * log10(255^2 / MSE)` in dB. Identical
+/// images return `Infinity`; lossy 8-bit results typically land between 30
+/// and 50 dB.
+#[wasm_bindgen]
+pub fn psnr(a: &[u8], b: &[u8], width: usize, height: usize) -> f64 {
+    let error = mse(a, b, width, height);
+    if error == 0.0 {
+        return f64::INFINITY;
+    }
+    10.0 * (255.0 * 255.0 / error).log10()
+}
+
+/// Per-pixel SSIM over a 7x7 window (clipped at the borders), following
+/// Wang et al. (2004) with `C1 = (0.01 * 255)^2` and `C2 = (0.03 * 255)^2`.
+/// Window means, variances and the covariance come from integral images.
+fn ssim_values(a: &[u8], b: &[u8], width: usize, height: usize) -> Vec<f32> {
+    let pixel_count = width * height;
+    if a.len() != pixel_count || b.len() != pixel_count {
+        panic!("Input array sizes don't match width * height");
+    }
+
+    const HALF: usize = 3;
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+    let integral_a = IntegralImage::new(a, width, height, true);
+    let integral_b = IntegralImage::new(b, width, height, true);
+    let integral_ab = IntegralImage::from_products(a, b, width, height);
+
+    let mut map = vec![0.0f32; pixel_count];
+    for y in 0..height {
+        for x in 0..width {
+            let (mean_a, var_a) = integral_a.window_mean_variance(x, y, HALF);
+            let (mean_b, var_b) = integral_b.window_mean_variance(x, y, HALF);
+            let (sum_ab, count) = integral_ab.window_sum(x, y, HALF);
+            let covariance = sum_ab as f64 / count as f64 - mean_a * mean_b;
+
+            let numerator = (2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2);
+            let denominator = (mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2);
+            map[y * width + x] = (numerator / denominator) as f32;
+        }
+    }
+
+    map
+}
+
+/// Structural similarity of two grayscale images: the mean of `ssim_map`.
+/// 1 means identical; values fall towards 0 (or below, for inverted
+/// structure) as local luminance, contrast and structure diverge.
+#[wasm_bindgen]
+pub fn ssim(a: &[u8], b: &[u8], width: usize, height: usize) -> f64 {
+    let map = ssim_values(a, b, width, height);
+    if map.is_empty() {
+        return 1.0;
+    }
+    map.iter().map(|&v| v as f64).sum::<f64>() / map.len() as f64
+}
+
+/// Per-pixel SSIM over a 7x7 window, as a `Float32Array`, showing where two
+/// images differ perceptually (low values) for debugging filter stages.
+#[wasm_bindgen]
+pub fn ssim_map(a: &[u8], b: &[u8], width: usize, height: usize) -> Vec<f32> {
+    ssim_values(a, b, width, height)
+}
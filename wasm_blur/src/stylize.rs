@@ -0,0 +1,41 @@
+use wasm_bindgen::prelude::*;
+use crate::border::BorderMode;
+use crate::convolution::convolve_2d;
+
+/// Emboss filter: a 3x3 directional derivative offset to mid-gray (128), so
+/// flat areas become gray and edges facing the light at `angle_deg` turn
+/// bright while the opposite edges turn dark.
+/// `angle_deg` is measured counter-clockwise from the positive x axis
+/// (0 = light from the right, 90 = light from the top).
+#[wasm_bindgen]
+pub fn emboss(input: &[u8], width: usize, height: usize, angle_deg: f32) -> Vec<u8> {
+    let (sin_a, cos_a) = angle_deg.to_radians().sin_cos();
+
+    // Weight each neighbour by its projection onto the light direction
+    // (image y grows downwards, hence the negated sine)
+    let mut kernel = [0.0f32; 9];
+    for ky in 0..3 {
+        for kx in 0..3 {
+            let dx = kx as f32 - 1.0;
+            let dy = ky as f32 - 1.0;
+            kernel[ky * 3 + kx] = dx * cos_a - dy * sin_a;
+        }
+    }
+
+    convolve_2d(input, width, height, &kernel, 3, 3, 128.0, BorderMode::Clamp)
+}
+
+/// Classic 3x3 Laplacian sharpen: `center * (1 + 4s) - s * (N + S + E + W)`.
+/// `strength` of 1.0 gives the common `[0,-1,0; -1,5,-1; 0,-1,0]` kernel;
+/// 0 leaves the image unchanged. For radius control use `unsharp_mask`.
+#[wasm_bindgen]
+pub fn sharpen(input: &[u8], width: usize, height: usize, strength: f32) -> Vec<u8> {
+    let s = strength;
+    let kernel = [
+        0.0, -s, 0.0,
+        -s, 1.0 + 4.0 * s, -s,
+        0.0, -s, 0.0,
+    ];
+
+    convolve_2d(input, width, height, &kernel, 3, 3, 0.0, BorderMode::Clamp)
+}
@@ -0,0 +1,107 @@
+use wasm_bindgen::prelude::*;
+use crate::canny::canny_edge_detector_full;
+use crate::gradient_calculation::calculate_sobel_gradients;
+
+/// Canny hysteresis thresholds (central-difference L1 gradients) used to find
+/// stroke boundaries; low enough to keep faint scene text.
+const SWT_CANNY_LOW: f32 = 20.0;
+const SWT_CANNY_HIGH: f32 = 60.0;
+
+/// Stroke Width Transform (Epshtein et al.) for text detection.
+/// From every Canny edge pixel a ray is cast along the gradient into the
+/// stroke (against the gradient for dark text on a light background) until
+/// it meets another edge pixel whose gradient points roughly back (within
+/// 30 degrees of opposite). Each pixel on an accepted ray records the ray
+/// length as its stroke width, keeping the minimum over all rays; a second
+/// pass caps every ray at its median so corners don't inflate the widths.
+///
+/// Connected regions with a consistent stroke width are likely characters.
+///
+/// # Returns
+/// `Float32Array` of stroke widths in pixels, 0 where no stroke was found
+#[wasm_bindgen]
+pub fn stroke_width_transform(input: &[u8], width: usize, height: usize, dark_on_light: bool) -> Vec<f32> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+    if width < 3 || height < 3 {
+        return vec![0.0; pixel_count];
+    }
+
+    let edges = canny_edge_detector_full(input, width, height, SWT_CANNY_LOW, SWT_CANNY_HIGH, 5, 1.0, false, false, 0);
+    let blurred = crate::blur(input, width, height, 5, 1.0);
+    let gradients = calculate_sobel_gradients(&blurred, width, height);
+
+    // Unit gradient direction per pixel, pointing into the stroke
+    let sign = if dark_on_light { -1.0 } else { 1.0 };
+    let direction = |idx: usize| -> Option<(f32, f32)> {
+        let gx = gradients[2 * idx] as f32;
+        let gy = gradients[2 * idx + 1] as f32;
+        let norm = (gx * gx + gy * gy).sqrt();
+        (norm > 0.0).then(|| (sign * gx / norm, sign * gy / norm))
+    };
+
+    let mut swt = vec![f32::INFINITY; pixel_count];
+    let mut rays: Vec<Vec<usize>> = Vec::new();
+    let max_steps = width.max(height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let start = y * width + x;
+            if edges[start] == 0 {
+                continue;
+            }
+            let Some((dx, dy)) = direction(start) else { continue };
+
+            let mut ray = vec![start];
+            let (mut px, mut py) = (x as f32 + 0.5, y as f32 + 0.5);
+            for _ in 0..max_steps {
+                px += dx;
+                py += dy;
+                if px < 0.0 || py < 0.0 || px >= width as f32 || py >= height as f32 {
+                    break;
+                }
+                let idx = py as usize * width + px as usize;
+                if idx == *ray.last().unwrap() {
+                    continue;
+                }
+                ray.push(idx);
+                if edges[idx] == 0 {
+                    continue;
+                }
+
+                // The opposite boundary must face back towards the start
+                if let Some((ex, ey)) = direction(idx) {
+                    if dx * ex + dy * ey < -(std::f32::consts::FRAC_PI_6.cos()) {
+                        let (sx, sy) = ((start % width) as f32, (start / width) as f32);
+                        let (qx, qy) = ((idx % width) as f32, (idx / width) as f32);
+                        let stroke = (qx - sx).hypot(qy - sy);
+                        for &p in &ray {
+                            swt[p] = swt[p].min(stroke);
+                        }
+                        rays.push(ray);
+                    }
+                }
+                break;
+            }
+        }
+    }
+
+    // Cap each ray at its median width
+    for ray in &rays {
+        let mut widths: Vec<f32> = ray.iter().map(|&p| swt[p]).collect();
+        let mid = widths.len() / 2;
+        let median = *widths.select_nth_unstable_by(mid, f32::total_cmp).1;
+        for &p in ray {
+            swt[p] = swt[p].min(median);
+        }
+    }
+
+    for v in swt.iter_mut() {
+        if !v.is_finite() {
+            *v = 0.0;
+        }
+    }
+    swt
+}
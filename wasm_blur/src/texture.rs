@@ -0,0 +1,165 @@
+use wasm_bindgen::prelude::*;
+use crate::unsharp_mask::bilinear_sample;
+
+/// Haralick texture features from a symmetric, normalized gray-level
+/// co-occurrence matrix (GLCM). Intensities are quantized to `levels` gray
+/// levels and pixel pairs are taken at offset (`dx`, `dy`), e.g. (1, 0) for
+/// horizontal neighbours.
+///
+/// Printed documents show high contrast and low homogeneity along text rows,
+/// while blank paper and smooth photo regions are highly homogeneous.
+///
+/// # Returns
+/// `[contrast, homogeneity]` where contrast is `sum(p(i,j) * (i-j)^2)` and
+/// homogeneity is `sum(p(i,j) / (1 + |i-j|))`, both on the quantized scale
+#[wasm_bindgen]
+pub fn glcm_features(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    dx: i32,
+    dy: i32,
+    levels: usize,
+) -> Vec<f64> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if !(2..=256).contains(&levels) {
+        panic!("levels must be between 2 and 256");
+    }
+
+    let mut glcm = vec![0u64; levels * levels];
+    let mut pairs = 0u64;
+
+    let x_start = (-dx).max(0) as usize;
+    let y_start = (-dy).max(0) as usize;
+    let x_end = (width as i64 - dx.max(0) as i64).max(0) as usize;
+    let y_end = (height as i64 - dy.max(0) as i64).max(0) as usize;
+
+    for y in y_start..y_end {
+        for x in x_start..x_end {
+            let a = input[y * width + x] as usize * levels / 256;
+            let nx = (x as i64 + dx as i64) as usize;
+            let ny = (y as i64 + dy as i64) as usize;
+            let b = input[ny * width + nx] as usize * levels / 256;
+            // Count both directions to make the matrix symmetric
+            glcm[a * levels + b] += 1;
+            glcm[b * levels + a] += 1;
+            pairs += 2;
+        }
+    }
+
+    if pairs == 0 {
+        return vec![0.0, 1.0];
+    }
+
+    let mut contrast = 0.0;
+    let mut homogeneity = 0.0;
+    for i in 0..levels {
+        for j in 0..levels {
+            let count = glcm[i * levels + j];
+            if count == 0 {
+                continue;
+            }
+            let p = count as f64 / pairs as f64;
+            let diff = i as f64 - j as f64;
+            contrast += p * diff * diff;
+            homogeneity += p / (1.0 + diff.abs());
+        }
+    }
+
+    vec![contrast, homogeneity]
+}
+
+/// Number of 0/1 transitions around a circular `points`-bit pattern.
+fn transitions(code: u32, points: usize) -> u32 {
+    let rotated = (code >> 1) | ((code & 1) << (points - 1));
+    (code ^ rotated).count_ones()
+}
+
+/// Local binary pattern code per pixel.
+/// `points` neighbours are sampled on a circle of `radius` pixels around each
+/// pixel (bilinearly, with clamped borders), starting to the right and going
+/// counter-clockwise; bit `i` is set when neighbour `i` is at least as bright
+/// as the center. Histograms of the codes are a compact texture descriptor,
+/// e.g. to tell printed text from photographs.
+///
+/// With `uniform` set, codes with at most two 0/1 transitions are numbered
+/// `0..N` in increasing code order and every other code maps to `N` (59
+/// labels for 8 points), as in Ojala et al.'s `u2` mapping.
+///
+/// # Arguments
+/// * `radius` - Circle radius in pixels (1 gives the classic 3x3 operator)
+/// * `points` - Number of neighbours, 1 to 8 so codes fit in a byte
+#[wasm_bindgen]
+pub fn local_binary_pattern(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    radius: f32,
+    points: usize,
+    uniform: bool,
+) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+    if !(1..=8).contains(&points) {
+        panic!("points must be between 1 and 8");
+    }
+
+    let offsets: Vec<(f32, f32)> = (0..points)
+        .map(|i| {
+            let angle = 2.0 * std::f32::consts::PI * i as f32 / points as f32;
+            // Image y grows downwards, so counter-clockwise means -sin
+            (radius * angle.cos(), -radius * angle.sin())
+        })
+        .collect();
+
+    let mut mapping: Vec<u8> = (0..1u32 << points).map(|c| c as u8).collect();
+    if uniform {
+        let mut next_label = 0u8;
+        let mut non_uniform = Vec::new();
+        for code in 0..1u32 << points {
+            if transitions(code, points) <= 2 {
+                mapping[code as usize] = next_label;
+                next_label += 1;
+            } else {
+                non_uniform.push(code);
+            }
+        }
+        for code in non_uniform {
+            mapping[code as usize] = next_label;
+        }
+    }
+
+    let mut output = vec![0u8; pixel_count];
+    for y in 0..height {
+        for x in 0..width {
+            let center = input[y * width + x] as f32;
+            let mut code = 0u32;
+            for (bit, &(dx, dy)) in offsets.iter().enumerate() {
+                let sx = x as f32 + dx;
+                let sy = y as f32 + dy;
+                let x_floor = sx.floor();
+                let y_floor = sy.floor();
+                let value = bilinear_sample(
+                    input,
+                    width,
+                    height,
+                    x_floor as isize,
+                    y_floor as isize,
+                    sx - x_floor,
+                    sy - y_floor,
+                );
+                // Tolerance keeps interpolated samples on the grid exact
+                if value >= center - 1e-3 {
+                    code |= 1 << bit;
+                }
+            }
+            output[y * width + x] = mapping[code as usize];
+        }
+    }
+
+    output
+}
@@ -0,0 +1,200 @@
+use wasm_bindgen::prelude::*;
+use crate::clahe::{column_weights, equalize_row};
+use crate::gaussian_blur::blur;
+use crate::histogram::{cdf_to_lut, clip_histogram};
+use crate::tiling::TileGrid;
+
+/// Bounds of a tile expanded by `halo` on every side and clipped to the image.
+/// Returns (x0, y0, x1, y1) in image coordinates, end-exclusive.
+fn padded_bounds(
+    image_width: usize,
+    image_height: usize,
+    tile_x: usize,
+    tile_y: usize,
+    tile_width: usize,
+    tile_height: usize,
+    halo: usize,
+) -> (usize, usize, usize, usize) {
+    if tile_x + tile_width > image_width || tile_y + tile_height > image_height {
+        panic!("Tile extends beyond the image");
+    }
+    (
+        tile_x.saturating_sub(halo),
+        tile_y.saturating_sub(halo),
+        (tile_x + tile_width + halo).min(image_width),
+        (tile_y + tile_height + halo).min(image_height),
+    )
+}
+
+/// Gaussian blur of one tile of a larger image, for streaming images that
+/// don't fit into WASM memory.
+///
+/// `padded_tile` holds the tile rectangle (`tile_x`, `tile_y`, `tile_width`,
+/// `tile_height`) expanded by `halo` pixels on every side and clipped to the
+/// image bounds. With `halo >= kernel_size / 2` the returned interior is
+/// identical to the same region of `blur` on the full image, so tiles can be
+/// stitched without seams.
+///
+/// # Returns
+/// Blurred tile interior, `tile_width * tile_height` bytes
+// The tile rectangle and halo are passed as plain numbers so JS needs no wrapper object
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn blur_tile(
+    padded_tile: &[u8],
+    image_width: usize,
+    image_height: usize,
+    tile_x: usize,
+    tile_y: usize,
+    tile_width: usize,
+    tile_height: usize,
+    halo: usize,
+    kernel_size: usize,
+    sigma: f32,
+) -> Vec<u8> {
+    if halo < kernel_size / 2 {
+        panic!("Halo must be at least kernel_size / 2");
+    }
+    let (x0, y0, x1, y1) = padded_bounds(
+        image_width, image_height, tile_x, tile_y, tile_width, tile_height, halo,
+    );
+    let padded_width = x1 - x0;
+    let padded_height = y1 - y0;
+    if padded_tile.len() != padded_width * padded_height {
+        panic!("Tile array size doesn't match the padded tile dimensions");
+    }
+
+    // Clamping at the padded edges only touches halo pixels, and clamping at
+    // real image edges matches the full-image blur.
+    let blurred = blur(padded_tile, padded_width, padded_height, kernel_size, sigma);
+
+    let off_x = tile_x - x0;
+    let off_y = tile_y - y0;
+    let mut output = Vec::with_capacity(tile_width * tile_height);
+    for y in 0..tile_height {
+        let start = (off_y + y) * padded_width + off_x;
+        output.extend_from_slice(&blurred[start..start + tile_width]);
+    }
+
+    output
+}
+
+/// Tile-streamed CLAHE for images too large to process in one call.
+///
+/// A halo cannot make CLAHE seamless, since every pixel depends on the
+/// histograms of whole neighbouring CLAHE tiles. Instead this works in two
+/// passes over the image:
+/// 1. `accumulate` every image tile (any tiling, no halo) to build the
+///    CLAHE tile histograms,
+/// 2. `apply` each image tile to get its equalized pixels.
+///
+/// Only `tile_grid_x * tile_grid_y * 256` counters are kept in memory, and the
+/// result equals `clahe` on the full image.
+#[wasm_bindgen]
+pub struct ClaheTiled {
+    grid: TileGrid,
+    clip_limit: f32,
+    histograms: Vec<u32>,
+    tile_cdfs: Vec<u8>,
+    finalized: bool,
+}
+
+#[wasm_bindgen]
+impl ClaheTiled {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        width: usize,
+        height: usize,
+        tile_grid_x: usize,
+        tile_grid_y: usize,
+        clip_limit: f32,
+    ) -> ClaheTiled {
+        let grid = TileGrid::new(width, height, tile_grid_x, tile_grid_y);
+        let num_tiles = grid.num_tiles();
+        ClaheTiled {
+            grid,
+            clip_limit,
+            histograms: vec![0u32; num_tiles * 256],
+            tile_cdfs: vec![0u8; num_tiles * 256],
+            finalized: false,
+        }
+    }
+
+    /// Adds the pixels of one image tile to the CLAHE tile histograms.
+    pub fn accumulate(
+        &mut self,
+        tile: &[u8],
+        tile_x: usize,
+        tile_y: usize,
+        tile_width: usize,
+        tile_height: usize,
+    ) {
+        if self.finalized {
+            panic!("Cannot accumulate after apply has been called");
+        }
+        padded_bounds(self.grid.width, self.grid.height, tile_x, tile_y, tile_width, tile_height, 0);
+        if tile.len() != tile_width * tile_height {
+            panic!("Tile array size doesn't match tile_width * tile_height");
+        }
+
+        for y in 0..tile_height {
+            for x in 0..tile_width {
+                let tile_idx = self.grid.tile_index_of(tile_x + x, tile_y + y);
+                self.histograms[tile_idx * 256 + tile[y * tile_width + x] as usize] += 1;
+            }
+        }
+    }
+
+    /// Converts the accumulated histograms into per-tile mapping tables.
+    fn finalize(&mut self) {
+        let tile_pixels = self.grid.tile_width * self.grid.tile_height;
+
+        let actual_clip = if self.clip_limit > 0.0 {
+            ((self.clip_limit * tile_pixels as f32) / 256.0).max(1.0) as u32
+        } else {
+            u32::MAX
+        };
+
+        for (tile_idx, (x_start, y_start, x_end, y_end)) in self.grid.tiles() {
+            let mut hist = [0u32; 256];
+            hist.copy_from_slice(&self.histograms[tile_idx * 256..(tile_idx + 1) * 256]);
+            let actual_tile_pixels = (y_end - y_start) * (x_end - x_start);
+
+            // Same clipping and CDF mapping as `clahe`
+            if actual_clip < u32::MAX {
+                clip_histogram(&mut hist, actual_clip);
+            }
+            let lut = cdf_to_lut(&hist, actual_tile_pixels as u32);
+            self.tile_cdfs[tile_idx * 256..(tile_idx + 1) * 256].copy_from_slice(&lut);
+        }
+
+        self.finalized = true;
+    }
+
+    /// Equalizes one image tile. Call after every tile has been accumulated.
+    pub fn apply(
+        &mut self,
+        tile: &[u8],
+        tile_x: usize,
+        tile_y: usize,
+        tile_width: usize,
+        tile_height: usize,
+    ) -> Vec<u8> {
+        padded_bounds(self.grid.width, self.grid.height, tile_x, tile_y, tile_width, tile_height, 0);
+        if tile.len() != tile_width * tile_height {
+            panic!("Tile array size doesn't match tile_width * tile_height");
+        }
+        if !self.finalized {
+            self.finalize();
+        }
+
+        let columns = column_weights(&self.grid, tile_x..tile_x + tile_width);
+        let mut output = vec![0u8; tile.len()];
+        for y in 0..tile_height {
+            let row = y * tile_width..(y + 1) * tile_width;
+            equalize_row(&tile[row.clone()], tile_y + y, &self.grid, &self.tile_cdfs, &columns, &mut output[row]);
+        }
+
+        output
+    }
+}
@@ -0,0 +1,94 @@
+/// A `tiles_x` x `tiles_y` grid of equal tiles over a `width` x `height`
+/// image, as used by CLAHE and the other tile-adaptive filters. The last
+/// row and column of tiles absorb the remainder when the image size isn't a
+/// multiple of the grid.
+///
+/// Per-tile values (mapping tables, thresholds) are blended bilinearly
+/// between the centers of the four nearest tiles, which avoids seams at tile
+/// borders; pixels closer to the image edge than half a tile use the edge
+/// tiles only.
+pub(crate) struct TileGrid {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) tiles_x: usize,
+    pub(crate) tiles_y: usize,
+    pub(crate) tile_width: usize,
+    pub(crate) tile_height: usize,
+}
+
+impl TileGrid {
+    pub(crate) fn new(width: usize, height: usize, tiles_x: usize, tiles_y: usize) -> TileGrid {
+        if tiles_x == 0 || tiles_y == 0 || tiles_x > width || tiles_y > height {
+            panic!("Tile grid must be between 1 and the image size in each direction");
+        }
+        TileGrid {
+            width,
+            height,
+            tiles_x,
+            tiles_y,
+            tile_width: width / tiles_x,
+            tile_height: height / tiles_y,
+        }
+    }
+
+    pub(crate) fn num_tiles(&self) -> usize {
+        self.tiles_x * self.tiles_y
+    }
+
+    /// Pixel bounds `(x0, y0, x1, y1)` of tile (`tx`, `ty`), end-exclusive.
+    pub(crate) fn tile_bounds(&self, tx: usize, ty: usize) -> (usize, usize, usize, usize) {
+        let x0 = tx * self.tile_width;
+        let y0 = ty * self.tile_height;
+        let x1 = if tx == self.tiles_x - 1 { self.width } else { x0 + self.tile_width };
+        let y1 = if ty == self.tiles_y - 1 { self.height } else { y0 + self.tile_height };
+        (x0, y0, x1, y1)
+    }
+
+    /// Every tile in row-major order as `(tile_index, bounds)`.
+    pub(crate) fn tiles(&self) -> impl Iterator<Item = (usize, (usize, usize, usize, usize))> + '_ {
+        (0..self.num_tiles()).map(move |i| (i, self.tile_bounds(i % self.tiles_x, i / self.tiles_x)))
+    }
+
+    /// Index of the tile containing pixel (`x`, `y`).
+    #[inline]
+    pub(crate) fn tile_index_of(&self, x: usize, y: usize) -> usize {
+        let tx = (x / self.tile_width).min(self.tiles_x - 1);
+        let ty = (y / self.tile_height).min(self.tiles_y - 1);
+        ty * self.tiles_x + tx
+    }
+
+    /// Neighbouring tile columns around pixel column `x` and the blend weight
+    /// of the second one.
+    #[inline]
+    pub(crate) fn x_weights(&self, x: usize) -> (usize, usize, f32) {
+        Self::axis_weights(x, self.tile_width, self.tiles_x)
+    }
+
+    /// Neighbouring tile rows around pixel row `y` and the blend weight of
+    /// the second one.
+    #[inline]
+    pub(crate) fn y_weights(&self, y: usize) -> (usize, usize, f32) {
+        Self::axis_weights(y, self.tile_height, self.tiles_y)
+    }
+
+    #[inline]
+    fn axis_weights(pos: usize, tile_size: usize, tiles: usize) -> (usize, usize, f32) {
+        // Map pixel position to tile center coordinates
+        let f = (pos as f32 / tile_size as f32) - 0.5;
+        let f_clamped = f.clamp(0.0, (tiles - 1) as f32);
+        let t0 = f_clamped.floor() as usize;
+        let t1 = (t0 + 1).min(tiles - 1);
+        (t0, t1, f_clamped - t0 as f32)
+    }
+
+    /// Bilinear blend of one value per tile (`values[tile_index]`) at pixel
+    /// (`x`, `y`).
+    #[inline]
+    pub(crate) fn interpolate_tile_values(&self, x: usize, y: usize, values: &[f32]) -> f32 {
+        let (tx0, tx1, wx) = self.x_weights(x);
+        let (ty0, ty1, wy) = self.y_weights(y);
+        let top = values[ty0 * self.tiles_x + tx0] * (1.0 - wx) + values[ty0 * self.tiles_x + tx1] * wx;
+        let bottom = values[ty1 * self.tiles_x + tx0] * (1.0 - wx) + values[ty1 * self.tiles_x + tx1] * wx;
+        top * (1.0 - wy) + bottom * wy
+    }
+}
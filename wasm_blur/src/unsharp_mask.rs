@@ -1,4 +1,11 @@
 use wasm_bindgen::prelude::*;
+use crate::roi::{check_roi, crop, paste};
+use crate::downscale::{area_bounds, bilinear_taps, nearest_index, SampleMode};
+use crate::rounding::RoundMode;
+use crate::integral_image::IntegralImage;
+
+#[cfg(all(target_arch = "wasm32", feature = "simd"))]
+use std::arch::wasm32::*;
 
 /// Unsharp mask: sharpened = original + amount * (original - blurred)
 /// Uses a box blur approximation for speed (separable, two-pass).
@@ -34,34 +41,170 @@ pub fn unsharp_mask(
         }
     }
 
-    // Vertical pass + unsharp mask combination
+    // Vertical pass + unsharp mask combination. Source rows are accumulated
+    // into a row of sums so the pass reads memory row by row.
+    let rounding = RoundMode::current();
     let mut output = vec![0u8; pixel_count];
-    for x in 0..width {
-        for y in 0..height {
-            let mut sum = 0u32;
-            let mut count = 0u32;
-            for k in -half_k..=half_k {
-                let ny = (y as isize + k).clamp(0, (height - 1) as isize) as usize;
-                sum += temp[ny * width + x] as u32;
-                count += 1;
-            }
-            let blurred = (sum / count) as f32;
+    let mut sums = vec![0u32; width];
+    for y in 0..height {
+        sums.fill(0);
+        for k in -half_k..=half_k {
+            let ny = (y as isize + k).clamp(0, (height - 1) as isize) as usize;
+            add_row_into(&mut sums, &temp[ny * width..(ny + 1) * width]);
+        }
+        for x in 0..width {
+            let blurred = (sums[x] / kernel_size as u32) as f32;
             let original = input[y * width + x] as f32;
             // Unsharp mask formula: sharpened = original + amount * (original - blurred)
             let sharpened = original + amount * (original - blurred);
-            output[y * width + x] = sharpened.round().clamp(0.0, 255.0) as u8;
+            output[y * width + x] = rounding.to_u8(sharpened);
         }
     }
 
     output
 }
 
-/// Fused unsharp mask + bilinear downscale in a single pass.
-/// For each output pixel, maps back to source coordinates, computes a local
-/// box blur in the source neighborhood, applies unsharp mask, and writes
-/// the result. This avoids allocating a full-resolution intermediate buffer.
+/// `unsharp_mask` restricted to the rectangle at (`roi_x`, `roi_y`) of size
+/// `roi_width` x `roi_height`. Only the ROI plus a `radius` halo is
+/// processed; the ROI equals the same region of `unsharp_mask` on the whole
+/// image and every pixel outside it is returned unchanged.
 ///
-/// Memory: only input (W*H) + output (tw*th) — no full-res intermediates.
+/// # Returns
+/// Full-size image with the ROI sharpened
+// The ROI rectangle is passed as plain numbers so JS needs no wrapper object
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen]
+pub fn unsharp_mask_roi(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    roi_x: usize,
+    roi_y: usize,
+    roi_width: usize,
+    roi_height: usize,
+    amount: f32,
+    radius: usize,
+) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    check_roi(width, height, roi_x, roi_y, roi_width, roi_height);
+    let mut output = input.to_vec();
+    if roi_width == 0 || roi_height == 0 {
+        return output;
+    }
+
+    // Edge clamping at the halo border only affects halo pixels, and at real
+    // image borders it matches the full-image filter
+    let x0 = roi_x.saturating_sub(radius);
+    let y0 = roi_y.saturating_sub(radius);
+    let x1 = (roi_x + roi_width + radius).min(width);
+    let y1 = (roi_y + roi_height + radius).min(height);
+    let padded = crop(input, width, x0, y0, x1 - x0, y1 - y0);
+    let sharpened = unsharp_mask(&padded, x1 - x0, y1 - y0, amount, radius);
+
+    let interior = crop(&sharpened, x1 - x0, roi_x - x0, roi_y - y0, roi_width, roi_height);
+    paste(&mut output, width, roi_x, roi_y, &interior, roi_width, roi_height);
+    output
+}
+
+/// Unsharp mask whose strength follows the local detail, so text edges are
+/// sharpened while flat paper and its noise are left mostly alone.
+/// The blurred value is the mean of the `(2 * radius + 1)^2` window and the
+/// detail measure its variance `v`, both from integral images. The noise
+/// variance `n` is estimated as the median local variance (most of a page is
+/// background), and each pixel is sharpened with `amount * max(v - n, 0) / v`,
+/// a Wiener-style gain that is ~0 on noise-level texture and tends to
+/// `amount` on strong edges.
+#[wasm_bindgen]
+pub fn adaptive_sharpen(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    amount: f32,
+    radius: usize,
+) -> Vec<u8> {
+    let pixel_count = width * height;
+    if input.len() != pixel_count {
+        panic!("Input array size doesn't match width * height");
+    }
+    if pixel_count == 0 {
+        return Vec::new();
+    }
+
+    let integral = IntegralImage::new(input, width, height, true);
+    let mut means = vec![0.0f32; pixel_count];
+    let mut variances = vec![0.0f32; pixel_count];
+    for y in 0..height {
+        for x in 0..width {
+            let (mean, variance) = integral.window_mean_variance(x, y, radius);
+            means[y * width + x] = mean as f32;
+            variances[y * width + x] = variance as f32;
+        }
+    }
+
+    let mut sorted = variances.clone();
+    let mid = pixel_count / 2;
+    let noise = *sorted.select_nth_unstable_by(mid, f32::total_cmp).1;
+
+    let rounding = RoundMode::current();
+    let mut output = vec![0u8; pixel_count];
+    for i in 0..pixel_count {
+        let variance = variances[i];
+        let gain = if variance > 0.0 { (variance - noise).max(0.0) / variance } else { 0.0 };
+        let original = input[i] as f32;
+        let sharpened = original + amount * gain * (original - means[i]);
+        output[i] = rounding.to_u8(sharpened);
+    }
+
+    output
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "simd"))]
+#[target_feature(enable = "simd128")]
+unsafe fn add_row_simd(sums: &mut [u32], row: &[u16]) {
+    let chunks = sums.len() / 8;
+    for chunk_idx in 0..chunks {
+        let x = chunk_idx * 8;
+        let values = v128_load(row.as_ptr().add(x) as *const v128);
+        let lo_ptr = sums.as_mut_ptr().add(x) as *mut v128;
+        let hi_ptr = sums.as_mut_ptr().add(x + 4) as *mut v128;
+        v128_store(lo_ptr, i32x4_add(v128_load(lo_ptr), u32x4_extend_low_u16x8(values)));
+        v128_store(hi_ptr, i32x4_add(v128_load(hi_ptr), u32x4_extend_high_u16x8(values)));
+    }
+    for x in (chunks * 8)..sums.len() {
+        sums[x] += row[x] as u32;
+    }
+}
+
+/// `sums[x] += row[x]`, 8 pixels at a time with the `simd` feature.
+fn add_row_into(sums: &mut [u32], row: &[u16]) {
+    #[cfg(all(target_arch = "wasm32", feature = "simd"))]
+    unsafe {
+        add_row_simd(sums, row);
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", feature = "simd")))]
+    for (sum, &v) in sums.iter_mut().zip(row) {
+        *sum += v as u32;
+    }
+}
+
+/// Fused unsharp mask + downscale in a single pass, sampling the source
+/// according to `sampling` (`Nearest` when omitted). This avoids allocating
+/// a full-resolution intermediate buffer.
+///
+/// * `Nearest` / `Bilinear` - the original value is the nearest source pixel
+///   or a blend of the four nearest, and the blurred value is a
+///   `(2 * radius + 1)^2` box around the nearest source pixel.
+/// * `Area` - the original value is the mean of the source pixels the output
+///   pixel covers and the blurred value the mean of that footprint grown by
+///   `radius` source pixels, both from per-row column sums. Large downscale
+///   factors don't alias.
+///
+/// Memory: input (W*H) + at most four rows + output (tw*th) — no full-res intermediates.
+// Exported to JS, where a positional argument list is the calling convention
+#[allow(clippy::too_many_arguments)]
 #[wasm_bindgen]
 pub fn unsharp_mask_and_downscale(
     input: &[u8],
@@ -71,6 +214,7 @@ pub fn unsharp_mask_and_downscale(
     target_height: usize,
     amount: f32,
     radius: usize,
+    sampling: Option<SampleMode>,
 ) -> Vec<u8> {
     let pixel_count = width * height;
     if input.len() != pixel_count {
@@ -82,33 +226,32 @@ pub fn unsharp_mask_and_downscale(
         return unsharp_mask(input, width, height, amount, radius);
     }
 
-    let half_k = radius as isize;
+    let sampling = sampling.unwrap_or(SampleMode::Nearest);
+    if sampling == SampleMode::Area {
+        return unsharp_area(input, width, height, target_width, target_height, amount, radius);
+    }
+
     let out_pixels = target_width * target_height;
     let mut output = vec![0u8; out_pixels];
 
-    // Scale factors for mapping output coords to source coords
-    let sx = width as f64 / target_width as f64;
-    let sy = height as f64 / target_height as f64;
-
+    let half_k = radius as isize;
+    let rounding = RoundMode::current();
     for oy in 0..target_height {
-        // Map output y to source y (center of the output pixel)
-        let src_y = (oy as f64 + 0.5) * sy - 0.5;
-        let src_y_floor = src_y.floor() as isize;
-        let fy = (src_y - src_y_floor as f64) as f32;
+        let (y0, y1, fy) = bilinear_taps(oy, height, target_height);
+        let iy = nearest_index(oy, height, target_height) as isize;
 
         for ox in 0..target_width {
-            // Map output x to source x
-            let src_x = (ox as f64 + 0.5) * sx - 0.5;
-            let src_x_floor = src_x.floor() as isize;
-            let fx = (src_x - src_x_floor as f64) as f32;
-
-            // Bilinear interpolation of the original pixel value
-            let original = bilinear_sample(input, width, height, src_x_floor, src_y_floor, fx, fy);
-
-            // Compute local blur at source position using box blur
-            // Sample the blur kernel centered at the source pixel
-            let iy = src_y.round() as isize;
-            let ix = src_x.round() as isize;
+            let ix = nearest_index(ox, width, target_width) as isize;
+            let original = if sampling == SampleMode::Bilinear {
+                let (x0, x1, fx) = bilinear_taps(ox, width, target_width);
+                let top = input[y0 * width + x0] as f32 * (1.0 - fx) + input[y0 * width + x1] as f32 * fx;
+                let bottom = input[y1 * width + x0] as f32 * (1.0 - fx) + input[y1 * width + x1] as f32 * fx;
+                top * (1.0 - fy) + bottom * fy
+            } else {
+                input[iy as usize * width + ix as usize] as f32
+            };
+
+            // Box blur centered at the nearest source pixel
             let mut blur_sum = 0u32;
             let mut blur_count = 0u32;
             for ky in -half_k..=half_k {
@@ -123,7 +266,66 @@ pub fn unsharp_mask_and_downscale(
 
             // Apply unsharp mask
             let sharpened = original + amount * (original - blurred);
-            output[oy * target_width + ox] = sharpened.round().clamp(0.0, 255.0) as u8;
+            output[oy * target_width + ox] = rounding.to_u8(sharpened);
+        }
+    }
+
+    output
+}
+
+/// `SampleMode::Area` path of `unsharp_mask_and_downscale`.
+fn unsharp_area(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    target_width: usize,
+    target_height: usize,
+    amount: f32,
+    radius: usize,
+) -> Vec<u8> {
+    // Prefix sums along x of the column sums over the footprint rows (inner)
+    // and the footprint rows grown by `radius` (outer)
+    let mut inner = vec![0u64; width + 1];
+    let mut outer = vec![0u64; width + 1];
+    let mut inner_cols = vec![0u64; width];
+    let mut outer_cols = vec![0u64; width];
+    let mut output = vec![0u8; target_width * target_height];
+    let rounding = RoundMode::current();
+
+    for oy in 0..target_height {
+        let (y0, y1) = area_bounds(oy, height, target_height);
+        let by0 = y0.saturating_sub(radius);
+        let by1 = (y1 + radius).min(height);
+
+        inner_cols.fill(0);
+        outer_cols.fill(0);
+        for y in by0..by1 {
+            let row = &input[y * width..(y + 1) * width];
+            let in_footprint = (y0..y1).contains(&y);
+            for x in 0..width {
+                let v = row[x] as u64;
+                outer_cols[x] += v;
+                if in_footprint {
+                    inner_cols[x] += v;
+                }
+            }
+        }
+        for x in 0..width {
+            inner[x + 1] = inner[x] + inner_cols[x];
+            outer[x + 1] = outer[x] + outer_cols[x];
+        }
+
+        for ox in 0..target_width {
+            let (x0, x1) = area_bounds(ox, width, target_width);
+            let bx0 = x0.saturating_sub(radius);
+            let bx1 = (x1 + radius).min(width);
+
+            let original = (inner[x1] - inner[x0]) as f32 / ((x1 - x0) * (y1 - y0)) as f32;
+            let blurred = (outer[bx1] - outer[bx0]) as f32 / ((bx1 - bx0) * (by1 - by0)) as f32;
+
+            // Apply unsharp mask
+            let sharpened = original + amount * (original - blurred);
+            output[oy * target_width + ox] = rounding.to_u8(sharpened);
         }
     }
 
@@ -132,7 +334,7 @@ pub fn unsharp_mask_and_downscale(
 
 /// Bilinear interpolation helper for sampling a grayscale image.
 #[inline]
-fn bilinear_sample(
+pub(crate) fn bilinear_sample(
     data: &[u8],
     width: usize,
     height: usize,
@@ -154,4 +356,43 @@ fn bilinear_sample(
     let top = p00 * (1.0 - fx) + p10 * fx;
     let bottom = p01 * (1.0 - fx) + p11 * fx;
     top * (1.0 - fy) + bottom * fy
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Variance over the rectangle `xs` x `ys`.
+    fn variance(image: &[u8], width: usize, xs: std::ops::Range<usize>, ys: std::ops::Range<usize>) -> f64 {
+        let values: Vec<f64> = ys.flat_map(|y| xs.clone().map(move |x| image[y * width + x] as f64)).collect();
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / values.len() as f64
+    }
+
+    #[test]
+    fn test_adaptive_sharpen_spares_flat_noise() {
+        // Vertical edge between 80 and 170, both sides with +-4 noise
+        let (width, height) = (64, 64);
+        let mut seed = 12345u32;
+        let input: Vec<u8> = (0..width * height)
+            .map(|i| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                let noise = (seed >> 16) % 9;
+                if i % width < 32 { 76 + noise as u8 } else { 166 + noise as u8 }
+            })
+            .collect();
+
+        let plain = unsharp_mask(&input, width, height, 1.5, 2);
+        let adaptive = adaptive_sharpen(&input, width, height, 1.5, 2);
+
+        let flat_plain = variance(&plain, width, 4..24, 4..60);
+        let flat_adaptive = variance(&adaptive, width, 4..24, 4..60);
+        assert!(flat_adaptive < flat_plain / 2.0, "{flat_adaptive} vs {flat_plain}");
+
+        // Contrast across the edge: the two pixels either side of it
+        let contrast = |image: &[u8]| -> f64 {
+            (4..60).map(|y| image[y * width + 32] as f64 - image[y * width + 31] as f64).sum::<f64>() / 56.0
+        };
+        let (edge_plain, edge_adaptive) = (contrast(&plain), contrast(&adaptive));
+        assert!((edge_adaptive - edge_plain).abs() < 0.15 * edge_plain, "{edge_adaptive} vs {edge_plain}");
+    }
+}
@@ -0,0 +1,138 @@
+use wasm_bindgen::prelude::*;
+use crate::rounding::RoundMode;
+
+/// Number of concentric rings `estimate_vignette` measures brightness in.
+const VIGNETTE_RINGS: usize = 16;
+
+/// Squared distance from the image center per column (or row), normalized
+/// so that the corners are at `x + y = 1`.
+fn normalized_sq_distances(len: usize) -> Vec<f32> {
+    let center = (len as f32 - 1.0) / 2.0;
+    let half = (len as f32 / 2.0).max(0.5);
+    (0..len)
+        .map(|i| {
+            let d = (i as f32 - center) / half;
+            d * d / 2.0
+        })
+        .collect()
+}
+
+/// Brightens darkened corners with the radial gain `1 + strength * r^2`,
+/// where `r` is the distance from the image center, 0 there and 1 at the
+/// corners. This inverts the usual falloff model `I = I0 / (1 + k r^2)` of
+/// wide-angle phone lenses, so `strength` is `k`: 0.3 lifts the corners by
+/// 30%. The gain factors are precomputed per row and column. Negative
+/// strengths darken the edges instead.
+#[wasm_bindgen]
+pub fn correct_vignette(input: &[u8], width: usize, height: usize, strength: f32) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let columns: Vec<f32> = normalized_sq_distances(width).into_iter().map(|d| strength * d).collect();
+    let rows = normalized_sq_distances(height);
+    let rounding = RoundMode::current();
+    let mut output = vec![0u8; width * height];
+    for (y, &dy) in rows.iter().enumerate() {
+        let row_gain = 1.0 + strength * dy;
+        let src = &input[y * width..(y + 1) * width];
+        for ((out, &v), &column_gain) in output[y * width..(y + 1) * width].iter_mut().zip(src).zip(&columns) {
+            *out = rounding.to_u8(v as f32 * (row_gain + column_gain));
+        }
+    }
+
+    output
+}
+
+/// Estimates the `strength` for `correct_vignette` from the image itself.
+/// The brightness of the background (the 90th percentile, so text and
+/// other dark content are ignored) is measured in rings around the center
+/// and the falloff model `B0 / (1 + k r^2)` is fitted to it by least
+/// squares on `1 / B`. Suits pages that fill most of the frame with light
+/// paper; returns 0 when no falloff is found.
+#[wasm_bindgen]
+pub fn estimate_vignette(input: &[u8], width: usize, height: usize) -> f32 {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let columns = normalized_sq_distances(width);
+    let rows = normalized_sq_distances(height);
+    let mut rings = vec![[0u32; 256]; VIGNETTE_RINGS];
+    for (y, &dy) in rows.iter().enumerate() {
+        for (&v, &dx) in input[y * width..(y + 1) * width].iter().zip(&columns) {
+            let ring = (((dx + dy) * VIGNETTE_RINGS as f32) as usize).min(VIGNETTE_RINGS - 1);
+            rings[ring][v as usize] += 1;
+        }
+    }
+
+    // (r^2 at the ring's middle, 1 / background brightness) per populated ring
+    let samples: Vec<(f64, f64)> = rings
+        .iter()
+        .enumerate()
+        .filter_map(|(i, hist)| {
+            let total: u32 = hist.iter().sum();
+            if total == 0 {
+                return None;
+            }
+            let target = (total as f64 * 0.9).ceil() as u32;
+            let mut running = 0;
+            let level = hist.iter().position(|&c| {
+                running += c;
+                running >= target
+            })?;
+            (level > 0).then(|| ((i as f64 + 0.5) / VIGNETTE_RINGS as f64, 1.0 / level as f64))
+        })
+        .collect();
+    if samples.len() < 2 {
+        return 0.0;
+    }
+
+    let n = samples.len() as f64;
+    let mean_r = samples.iter().map(|s| s.0).sum::<f64>() / n;
+    let mean_inv = samples.iter().map(|s| s.1).sum::<f64>() / n;
+    let covariance: f64 = samples.iter().map(|s| (s.0 - mean_r) * (s.1 - mean_inv)).sum();
+    let variance: f64 = samples.iter().map(|s| (s.0 - mean_r) * (s.0 - mean_r)).sum();
+    let slope = covariance / variance;
+    let intercept = mean_inv - slope * mean_r;
+    if intercept <= 0.0 {
+        return 0.0;
+    }
+
+    (slope / intercept).max(0.0) as f32
+}
+
+/// `correct_vignette` with the strength from `estimate_vignette`.
+#[wasm_bindgen]
+pub fn correct_vignette_auto(input: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let strength = estimate_vignette(input, width, height);
+    correct_vignette(input, width, height, strength)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vignette_round_trip() {
+        // Flat paper darkened by a 0.4 falloff, with a dark line of text
+        let (width, height) = (120, 80);
+        let (columns, rows) = (normalized_sq_distances(width), normalized_sq_distances(height));
+        let falloff: Vec<u8> = (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                if (38..42).contains(&y) && (20..100).contains(&x) {
+                    30
+                } else {
+                    (200.0 / (1.0 + 0.4 * (columns[x] + rows[y]))).round() as u8
+                }
+            })
+            .collect();
+
+        let strength = estimate_vignette(&falloff, width, height);
+        assert!((strength - 0.4).abs() < 0.05, "{}", strength);
+
+        let corrected = correct_vignette(&falloff, width, height, strength);
+        assert!(corrected[0].abs_diff(200) <= 6 && corrected[40 * width + 10].abs_diff(200) <= 6);
+    }
+}
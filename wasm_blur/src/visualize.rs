@@ -0,0 +1,120 @@
+use wasm_bindgen::prelude::*;
+
+/// Tints the pixels of an RGBA image where `mask` is non-zero.
+/// Each masked pixel becomes `(1 - opacity) * pixel + opacity * color`;
+/// alpha and unmasked pixels are copied unchanged.
+///
+/// # Arguments
+/// * `image_rgba` - RGBA image, `width * height * 4` bytes
+/// * `mask` - Single-channel mask, `width * height` bytes
+/// * `color` - Tint as `[r, g, b]`
+/// * `opacity` - Blend factor in [0, 1]
+#[wasm_bindgen]
+pub fn overlay_mask(
+    image_rgba: &[u8],
+    width: usize,
+    height: usize,
+    mask: &[u8],
+    color: &[u8],
+    opacity: f32,
+) -> Vec<u8> {
+    let pixel_count = width * height;
+    if image_rgba.len() != pixel_count * 4 {
+        panic!("Image array size doesn't match width * height * 4");
+    }
+    if mask.len() != pixel_count {
+        panic!("Mask array size doesn't match width * height");
+    }
+    if color.len() != 3 {
+        panic!("Color must have exactly 3 components");
+    }
+
+    let alpha = opacity.clamp(0.0, 1.0);
+    let mut output = image_rgba.to_vec();
+    for (px, &m) in output.chunks_exact_mut(4).zip(mask.iter()) {
+        if m == 0 {
+            continue;
+        }
+        for c in 0..3 {
+            let blended = px[c] as f32 * (1.0 - alpha) + color[c] as f32 * alpha;
+            px[c] = blended.round().clamp(0.0, 255.0) as u8;
+        }
+    }
+
+    output
+}
+
+/// False-color palettes for `apply_colormap`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Colormap {
+    Grayscale,
+    Jet,
+    Hot,
+    Viridis,
+}
+
+// Polynomial fit of matplotlib's viridis (Matt Zucker), evaluated per channel
+const VIRIDIS_COEFFS: [[f32; 3]; 7] = [
+    [0.277_727_33, 0.005_407_344_5, 0.334_099_8],
+    [0.105_093_04, 1.404_613_5, 1.384_590_2],
+    [-0.330_861_83, 0.214_847_56, 0.095_095_16],
+    [-4.634_230_5, -5.799_101, -19.332_441],
+    [6.228_27, 14.179_933, 56.690_55],
+    [4.776_385, -13.745_145, -65.353_03],
+    [-5.435_456, 4.645_852_6, 26.312_435],
+];
+
+/// RGB color for `t` in [0, 1] under the given palette.
+fn colormap_rgb(map: Colormap, t: f32) -> [f32; 3] {
+    match map {
+        Colormap::Grayscale => [t, t, t],
+        Colormap::Jet => [
+            (1.5 - (4.0 * t - 3.0).abs()).clamp(0.0, 1.0),
+            (1.5 - (4.0 * t - 2.0).abs()).clamp(0.0, 1.0),
+            (1.5 - (4.0 * t - 1.0).abs()).clamp(0.0, 1.0),
+        ],
+        Colormap::Hot => [
+            (3.0 * t).clamp(0.0, 1.0),
+            (3.0 * t - 1.0).clamp(0.0, 1.0),
+            (3.0 * t - 2.0).clamp(0.0, 1.0),
+        ],
+        Colormap::Viridis => {
+            let mut rgb = [0.0f32; 3];
+            for (c, value) in rgb.iter_mut().enumerate() {
+                // Horner evaluation from the highest coefficient down
+                let mut acc = VIRIDIS_COEFFS[6][c];
+                for k in (0..6).rev() {
+                    acc = acc * t + VIRIDIS_COEFFS[k][c];
+                }
+                *value = acc.clamp(0.0, 1.0);
+            }
+            rgb
+        }
+    }
+}
+
+/// Maps a single-channel image through a 256-entry false-color palette and
+/// returns an opaque RGBA image. Useful for inspecting scalar outputs such as
+/// gradient magnitudes or distance maps.
+#[wasm_bindgen]
+pub fn apply_colormap(input: &[u8], width: usize, height: usize, map: Colormap) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+
+    let mut palette = [[0u8; 3]; 256];
+    for (i, entry) in palette.iter_mut().enumerate() {
+        let rgb = colormap_rgb(map, i as f32 / 255.0);
+        for c in 0..3 {
+            entry[c] = (rgb[c] * 255.0).round() as u8;
+        }
+    }
+
+    let mut output = vec![255u8; input.len() * 4];
+    for (px, &v) in output.chunks_exact_mut(4).zip(input.iter()) {
+        px[..3].copy_from_slice(&palette[v as usize]);
+    }
+
+    output
+}
@@ -0,0 +1,425 @@
+use wasm_bindgen::prelude::*;
+use crate::rounding::RoundMode;
+
+/// Inverts a 2x3 affine matrix `[a, b, c, d, e, f]` (`x' = a*x + b*y + c`,
+/// `y' = d*x + e*y + f`), or `None` when it is singular.
+fn invert_affine(m: &[f32]) -> Option<[f64; 6]> {
+    let [a, b, c, d, e, f] = [m[0], m[1], m[2], m[3], m[4], m[5]].map(|v| v as f64);
+    let det = a * e - b * d;
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let (ia, ib, id, ie) = (e / det, -b / det, -d / det, a / det);
+    Some([ia, ib, -(ia * c + ib * f), id, ie, -(id * c + ie * f)])
+}
+
+/// Bilinear sample at (`x`, `y`) in pixel-center coordinates; taps outside
+/// the image read `fill`.
+#[inline]
+fn sample_or_fill(input: &[u8], width: usize, height: usize, x: f64, y: f64, fill: u8) -> f32 {
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let fx = (x - x0) as f32;
+    let fy = (y - y0) as f32;
+    let tap = |tx: f64, ty: f64| -> f32 {
+        if tx < 0.0 || ty < 0.0 || tx >= width as f64 || ty >= height as f64 {
+            fill as f32
+        } else {
+            input[ty as usize * width + tx as usize] as f32
+        }
+    };
+
+    let top = tap(x0, y0) * (1.0 - fx) + tap(x0 + 1.0, y0) * fx;
+    let bottom = tap(x0, y0 + 1.0) * (1.0 - fx) + tap(x0 + 1.0, y0 + 1.0) * fx;
+    top * (1.0 - fy) + bottom * fy
+}
+
+/// Fills an `out_width` x `out_height` image by sampling the source at
+/// `source_of(ox, oy)` for every output pixel.
+fn warp_with(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    out_width: usize,
+    out_height: usize,
+    fill: u8,
+    source_of: impl Fn(f64, f64) -> (f64, f64),
+) -> Vec<u8> {
+    let rounding = RoundMode::current();
+    let mut output = vec![fill; out_width * out_height];
+    for oy in 0..out_height {
+        for ox in 0..out_width {
+            let (sx, sy) = source_of(ox as f64, oy as f64);
+            // Also rejects NaN from points mapped to infinity
+            if !(sx > -1.0 && sy > -1.0 && sx < width as f64 && sy < height as f64) {
+                continue;
+            }
+            let value = sample_or_fill(input, width, height, sx, sy, fill);
+            output[oy * out_width + ox] = rounding.to_u8(value);
+        }
+    }
+
+    output
+}
+
+/// Affine warp of a grayscale image.
+/// `matrix` is the 2x3 forward transform `[a, b, c, d, e, f]` mapping a source
+/// pixel (x, y) to (`a*x + b*y + c`, `d*x + e*y + f`) in the output, as
+/// OpenCV's `warpAffine`, so rotation, scale, shear and translation combine in
+/// one call. Each output pixel is inverse-mapped into the source and sampled
+/// bilinearly; samples that fall outside the source blend towards `fill`.
+///
+/// # Returns
+/// `out_width` x `out_height` warped image
+#[wasm_bindgen]
+pub fn warp_affine(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    matrix: &[f32],
+    out_width: usize,
+    out_height: usize,
+    fill: u8,
+) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if matrix.len() != 6 {
+        panic!("Affine matrix must have 6 elements");
+    }
+    let inv = match invert_affine(matrix) {
+        Some(inv) => inv,
+        None => panic!("Affine matrix is not invertible"),
+    };
+
+    warp_with(input, width, height, out_width, out_height, fill, |x, y| {
+        (inv[0] * x + inv[1] * y + inv[2], inv[3] * x + inv[4] * y + inv[5])
+    })
+}
+
+/// Solves `a * x = b` for an `n` x `n` system stored row-major, with partial
+/// pivoting. `None` when the system is (numerically) singular.
+fn solve_linear(mut a: Vec<f64>, mut b: Vec<f64>, n: usize) -> Option<Vec<f64>> {
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i * n + col].abs().total_cmp(&a[j * n + col].abs()))?;
+        if a[pivot * n + col].abs() < 1e-10 {
+            return None;
+        }
+        if pivot != col {
+            for k in 0..n {
+                a.swap(pivot * n + k, col * n + k);
+            }
+            b.swap(pivot, col);
+        }
+        for row in col + 1..n {
+            let factor = a[row * n + col] / a[col * n + col];
+            for k in col..n {
+                a[row * n + k] -= factor * a[col * n + k];
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let tail: f64 = (row + 1..n).map(|k| a[row * n + k] * x[k]).sum();
+        x[row] = (b[row] - tail) / a[row * n + row];
+    }
+    Some(x)
+}
+
+/// True when some three of the four points lie on one line (relative to the
+/// quad's size), which leaves the homography undetermined.
+fn has_collinear_triple(points: &[f64; 8]) -> bool {
+    let p = |i: usize| (points[2 * i], points[2 * i + 1]);
+    let extent = (0..4)
+        .flat_map(|i| (0..4).map(move |j| (i, j)))
+        .map(|(i, j)| (p(i).0 - p(j).0).hypot(p(i).1 - p(j).1))
+        .fold(0.0, f64::max);
+    let tolerance = 1e-6 * extent * extent;
+    [(0, 1, 2), (0, 1, 3), (0, 2, 3), (1, 2, 3)].iter().any(|&(i, j, k)| {
+        let (a, b, c) = (p(i), p(j), p(k));
+        let cross = (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+        cross.abs() <= tolerance
+    })
+}
+
+/// Homography mapping four source points onto four destination points.
+/// `src` and `dst` hold the points as `[x0, y0, x1, y1, x2, y2, x3, y3]`.
+/// The 3x3 matrix is solved from the standard 8-equation linear system with
+/// the bottom-right entry fixed to 1, and returned row-major, ready for
+/// `warp_perspective` or `perspective_transform_points`.
+///
+/// # Returns
+/// The 9 matrix entries, or an empty array when three of the points in
+/// either set are collinear (or otherwise degenerate)
+#[wasm_bindgen]
+pub fn find_homography(src: &[f32], dst: &[f32]) -> Vec<f32> {
+    if src.len() != 8 || dst.len() != 8 {
+        panic!("Homography needs exactly four points (8 coordinates) in src and dst");
+    }
+    let src: [f64; 8] = std::array::from_fn(|i| src[i] as f64);
+    let dst: [f64; 8] = std::array::from_fn(|i| dst[i] as f64);
+    if has_collinear_triple(&src) || has_collinear_triple(&dst) {
+        return Vec::new();
+    }
+
+    // For each pair: u = (h0 x + h1 y + h2) / (h6 x + h7 y + 1), same for v
+    let mut a = vec![0.0f64; 64];
+    let mut b = vec![0.0f64; 8];
+    for i in 0..4 {
+        let (x, y) = (src[2 * i], src[2 * i + 1]);
+        let (u, v) = (dst[2 * i], dst[2 * i + 1]);
+        a[(2 * i) * 8..(2 * i + 1) * 8].copy_from_slice(&[x, y, 1.0, 0.0, 0.0, 0.0, -u * x, -u * y]);
+        a[(2 * i + 1) * 8..(2 * i + 2) * 8].copy_from_slice(&[0.0, 0.0, 0.0, x, y, 1.0, -v * x, -v * y]);
+        b[2 * i] = u;
+        b[2 * i + 1] = v;
+    }
+
+    match solve_linear(a, b, 8) {
+        Some(h) => h.iter().map(|&v| v as f32).chain(std::iter::once(1.0)).collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Inverts a row-major 3x3 matrix, or `None` when it is singular.
+fn invert_3x3(m: &[f32]) -> Option<[f64; 9]> {
+    let m: [f64; 9] = std::array::from_fn(|i| m[i] as f64);
+    let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| {
+        m[r0 * 3 + c0] * m[r1 * 3 + c1] - m[r0 * 3 + c1] * m[r1 * 3 + c0]
+    };
+    let adj = [
+        cofactor(1, 2, 1, 2),
+        -cofactor(0, 2, 1, 2),
+        cofactor(0, 1, 1, 2),
+        -cofactor(1, 2, 0, 2),
+        cofactor(0, 2, 0, 2),
+        -cofactor(0, 1, 0, 2),
+        cofactor(1, 2, 0, 1),
+        -cofactor(0, 2, 0, 1),
+        cofactor(0, 1, 0, 1),
+    ];
+    let det = m[0] * adj[0] + m[1] * adj[3] + m[2] * adj[6];
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    Some(adj.map(|v| v / det))
+}
+
+/// Perspective warp of a grayscale image.
+/// `matrix` is a row-major 3x3 homography mapping source pixels into the
+/// output (as returned by `find_homography`, and as OpenCV's
+/// `warpPerspective`). Output pixels are inverse-mapped and sampled
+/// bilinearly; samples outside the source blend towards `fill`.
+///
+/// # Returns
+/// `out_width` x `out_height` warped image
+#[wasm_bindgen]
+pub fn warp_perspective(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    matrix: &[f32],
+    out_width: usize,
+    out_height: usize,
+    fill: u8,
+) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if matrix.len() != 9 {
+        panic!("Homography matrix must have 9 elements");
+    }
+    let inv = match invert_3x3(matrix) {
+        Some(inv) => inv,
+        None => panic!("Homography matrix is not invertible"),
+    };
+
+    warp_with(input, width, height, out_width, out_height, fill, |x, y| {
+        let w = inv[6] * x + inv[7] * y + inv[8];
+        ((inv[0] * x + inv[1] * y + inv[2]) / w, (inv[3] * x + inv[4] * y + inv[5]) / w)
+    })
+}
+
+/// Output size for rectifying `corners` to `aspect` (width / height): the
+/// detected quad's size is estimated as the mean of each pair of opposite
+/// sides, and its area is kept while the shape is forced to `aspect`. Taking
+/// the area rather than either side alone halves the effect of a poor
+/// perspective estimate in one direction.
+fn aspect_dimensions(corners: &[f32], aspect: f32) -> (usize, usize) {
+    let p = |i: usize| (corners[2 * i] as f64, corners[2 * i + 1] as f64);
+    let side = |i: usize, j: usize| (p(i).0 - p(j).0).hypot(p(i).1 - p(j).1);
+    let width = 0.5 * (side(0, 1) + side(3, 2));
+    let height = 0.5 * (side(0, 3) + side(1, 2));
+    let area = width * height;
+    let aspect = aspect as f64;
+    let out_width = (area * aspect).sqrt().round().max(1.0) as usize;
+    let out_height = (area / aspect).sqrt().round().max(1.0) as usize;
+    (out_width, out_height)
+}
+
+/// Rectifies a detected document to a known aspect ratio (A4 portrait is
+/// `1 / 2f32.sqrt()`, US Letter `8.5 / 11.0`, ...). `corners` are
+/// `[x0, y0, ..., x3, y3]` in top-left, top-right, bottom-right, bottom-left
+/// order; `aspect` is the output width / height. The output keeps the
+/// detected quad's estimated area, so resolution follows the photo, while its
+/// proportions come from `aspect` alone.
+///
+/// # Returns
+/// The rectified image, `out_width` x `out_height` as given by the quad area
+/// and `aspect`, or an empty array when the corners are degenerate
+#[wasm_bindgen]
+pub fn rectify_to_aspect(input: &[u8], width: usize, height: usize, corners: &[f32], aspect: f32) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if corners.len() != 8 {
+        panic!("Corners must hold four points (8 coordinates)");
+    }
+    if !(aspect > 0.0 && aspect.is_finite()) {
+        panic!("Aspect ratio must be positive");
+    }
+
+    let (out_width, out_height) = aspect_dimensions(corners, aspect);
+    let (right, bottom) = ((out_width - 1) as f32, (out_height - 1) as f32);
+    let target = [0.0, 0.0, right, 0.0, right, bottom, 0.0, bottom];
+    let h = find_homography(corners, &target);
+    if h.is_empty() {
+        return Vec::new();
+    }
+    warp_perspective(input, width, height, &h, out_width, out_height, 0)
+}
+
+/// Removes lens distortion with the Brown-Conrady model (OpenCV's
+/// `undistort`), so the straight edges of a page that a wide-angle lens bows
+/// come out straight for corner detection. `coefficients` is
+/// `[k1, k2, p1, p2]` and `camera` the camera matrix `[fx, fy, cx, cy]` from
+/// calibration (e.g. with `find_chessboard_corners`), in pixels.
+///
+/// Each output pixel is normalized by the camera matrix, pushed through the
+/// distortion model (radial `1 + k1 r^2 + k2 r^4` plus the tangential `p1`,
+/// `p2` terms) to find where the lens imaged it, and sampled there
+/// bilinearly. The output uses the same camera matrix and size as the
+/// input; areas the lens never imaged read `fill`.
+#[wasm_bindgen]
+pub fn undistort(
+    input: &[u8],
+    width: usize,
+    height: usize,
+    coefficients: &[f32],
+    camera: &[f32],
+    fill: u8,
+) -> Vec<u8> {
+    if input.len() != width * height {
+        panic!("Input array size doesn't match width * height");
+    }
+    if coefficients.len() != 4 {
+        panic!("Distortion coefficients must have 4 elements");
+    }
+    if camera.len() != 4 {
+        panic!("Camera matrix must have 4 elements");
+    }
+    if camera[0] == 0.0 || camera[1] == 0.0 {
+        panic!("Focal lengths must be non-zero");
+    }
+
+    let [k1, k2, p1, p2] = std::array::from_fn(|i| coefficients[i] as f64);
+    let [fx, fy, cx, cy] = std::array::from_fn(|i| camera[i] as f64);
+    warp_with(input, width, height, width, height, fill, |u, v| {
+        let (x, y) = ((u - cx) / fx, (v - cy) / fy);
+        let r2 = x * x + y * y;
+        let radial = 1.0 + k1 * r2 + k2 * r2 * r2;
+        let xd = x * radial + 2.0 * p1 * x * y + p2 * (r2 + 2.0 * x * x);
+        let yd = y * radial + p1 * (r2 + 2.0 * y * y) + 2.0 * p2 * x * y;
+        (fx * xd + cx, fy * yd + cy)
+    })
+}
+
+/// Maps interleaved `[x0, y0, x1, y1, ...]` points through a row-major 3x3
+/// homography, e.g. annotation coordinates into the space produced by
+/// `warp_perspective` with the same matrix (or back, with the homography
+/// from `find_homography(dst, src)`). Points that map to infinity come out
+/// non-finite.
+#[wasm_bindgen]
+pub fn perspective_transform_points(points: &[f32], matrix: &[f32]) -> Vec<f32> {
+    if !points.len().is_multiple_of(2) {
+        panic!("Points array must hold (x, y) pairs");
+    }
+    if matrix.len() != 9 {
+        panic!("Homography matrix must have 9 elements");
+    }
+
+    let m: [f64; 9] = std::array::from_fn(|i| matrix[i] as f64);
+    let mut output = Vec::with_capacity(points.len());
+    for point in points.chunks_exact(2) {
+        let (x, y) = (point[0] as f64, point[1] as f64);
+        let w = m[6] * x + m[7] * y + m[8];
+        output.push(((m[0] * x + m[1] * y + m[2]) / w) as f32);
+        output.push(((m[3] * x + m[4] * y + m[5]) / w) as f32);
+    }
+    output
+}
+
+/// Maps interleaved `[x0, y0, x1, y1, ...]` points through a 2x3 affine
+/// matrix `[a, b, c, d, e, f]`, the same convention as `warp_affine`.
+#[wasm_bindgen]
+pub fn affine_transform_points(points: &[f32], matrix: &[f32]) -> Vec<f32> {
+    if !points.len().is_multiple_of(2) {
+        panic!("Points array must hold (x, y) pairs");
+    }
+    if matrix.len() != 6 {
+        panic!("Affine matrix must have 6 elements");
+    }
+
+    let mut output = Vec::with_capacity(points.len());
+    for point in points.chunks_exact(2) {
+        let (x, y) = (point[0], point[1]);
+        output.push(matrix[0] * x + matrix[1] * y + matrix[2]);
+        output.push(matrix[3] * x + matrix[4] * y + matrix[5]);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_homography_maps_corners() {
+        let src = [10.0, 20.0, 200.0, 30.0, 210.0, 300.0, 5.0, 280.0];
+        let dst = [0.0, 0.0, 100.0, 0.0, 100.0, 140.0, 0.0, 140.0];
+        let h = find_homography(&src, &dst);
+        assert_eq!(h.len(), 9);
+        let mapped = perspective_transform_points(&src, &h);
+        for (m, d) in mapped.iter().zip(&dst) {
+            assert!((m - d).abs() < 1e-3);
+        }
+
+        let collinear = [0.0, 0.0, 1.0, 1.0, 2.0, 2.0, 0.0, 5.0];
+        assert!(find_homography(&collinear, &dst).is_empty());
+    }
+
+    #[test]
+    fn test_rectify_to_aspect_keeps_area() {
+        let (width, height) = (64, 64);
+        let input = vec![200u8; width * height];
+        // 40 x 10 rectangle forced to a 1:1 aspect keeps its 400 px area
+        let corners = [10.0, 20.0, 50.0, 20.0, 50.0, 30.0, 10.0, 30.0];
+        let output = rectify_to_aspect(&input, width, height, &corners, 1.0);
+        assert_eq!(output.len(), 20 * 20);
+        assert!(output.iter().all(|&v| v == 200));
+    }
+
+    #[test]
+    fn test_undistort() {
+        let (width, height) = (40, 30);
+        let input: Vec<u8> = (0..width * height).map(|i| (i % 251) as u8).collect();
+        assert_eq!(undistort(&input, width, height, &[0.0; 4], &[50.0, 50.0, 20.0, 15.0], 0), input);
+
+        // Pincushion (k1 > 0) samples outside the frame near the corners,
+        // while the principal point stays put
+        let output = undistort(&input, width, height, &[0.5, 0.0, 0.0, 0.0], &[20.0, 20.0, 20.0, 15.0], 7);
+        assert_eq!(output[0], 7);
+        assert_eq!(output[15 * width + 20], input[15 * width + 20]);
+    }
+}